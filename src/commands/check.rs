@@ -0,0 +1,332 @@
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::{
+    error::SnipsterError,
+    storage::{
+        file::{Snippet, Snipster},
+        placeholder::PlaceHolder,
+        requirements::is_installed,
+    },
+};
+
+const FIELDS: &[&str] = &[
+    "name",
+    "content",
+    "note",
+    "placeholders",
+    "sources",
+    "danger",
+    "requires",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug)]
+struct Issue {
+    line: usize,
+    severity: Severity,
+    snippet: Option<String>, // `category/name`
+    message: String,
+}
+
+/// Lints a snippet library for CI and prints one `file:line: severity: message`
+/// per problem. Fails when any problem is an error; warnings only get reported.
+pub fn check(path: &str, shellcheck: bool) -> Result<Snipster, SnipsterError> {
+    let contents = fs::read_to_string(path)?;
+    let mut issues = Vec::new();
+
+    match serde_json::from_str::<Value>(&contents) {
+        Ok(library) => check_library(&contents, &library, shellcheck, &mut issues)?,
+        Err(e) => issues.push(Issue {
+            line: e.line(),
+            severity: Severity::Error,
+            snippet: None,
+            message: format!("invalid JSON: {}", e),
+        }),
+    }
+
+    issues.sort_by_key(|issue| (issue.line, issue.severity));
+
+    for issue in &issues {
+        let (label, color) = match issue.severity {
+            Severity::Error => ("error", "91"),
+            Severity::Warning => ("warning", "33"),
+        };
+        let snippet = issue
+            .snippet
+            .as_ref()
+            .map(|snippet| format!("{}: ", snippet))
+            .unwrap_or_default();
+        println!(
+            "{}:{}: \x1b[{}m{}\x1b[0m: {}{}",
+            path, issue.line, color, label, snippet, issue.message
+        );
+    }
+
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    println!("{} error(s), {} warning(s)", errors, issues.len() - errors);
+
+    if errors > 0 {
+        return Err(SnipsterError::ChecksFailed(errors));
+    }
+
+    Ok(Snipster { snippet: None })
+}
+
+fn check_library(
+    contents: &str,
+    library: &Value,
+    shellcheck: bool,
+    issues: &mut Vec<Issue>,
+) -> Result<(), SnipsterError> {
+    let Some(categories) = library.as_object() else {
+        issues.push(Issue {
+            line: 1,
+            severity: Severity::Error,
+            snippet: None,
+            message: "expected an object of categories".into(),
+        });
+        return Ok(());
+    };
+
+    if shellcheck && !is_installed("shellcheck") {
+        return Err(SnipsterError::CommandError(
+            "shellcheck is not installed".into(),
+        ));
+    }
+
+    for (category, snippets) in categories {
+        let category_line = locate(contents, &category_pattern(category)).unwrap_or(1);
+
+        let Some(snippets) = snippets.as_array() else {
+            issues.push(Issue {
+                line: category_line,
+                severity: Severity::Error,
+                snippet: None,
+                message: format!("category `{}` must be an array of snippets", category),
+            });
+            continue;
+        };
+
+        let mut seen: HashMap<String, usize> = HashMap::new();
+
+        for (index, value) in snippets.iter().enumerate() {
+            let name = value.get("name").and_then(Value::as_str);
+            let occurrence = name
+                .map(|name| {
+                    let count = seen.entry(name.to_string()).or_default();
+                    *count += 1;
+                    *count - 1
+                })
+                .unwrap_or(0);
+
+            // Snippets are found by their `name` after the category key; one without
+            // a name is reported at its category.
+            let line = name
+                .and_then(|name| snippet_line(contents, category, name, occurrence))
+                .unwrap_or(category_line);
+            let label = format!("{}/{}", category, name.unwrap_or(&format!("#{}", index)));
+            let mut report = |severity, message: String| {
+                issues.push(Issue {
+                    line,
+                    severity,
+                    snippet: Some(label.clone()),
+                    message,
+                })
+            };
+
+            if occurrence > 0 {
+                report(
+                    Severity::Error,
+                    format!("duplicate name in category `{}`", category),
+                );
+            }
+
+            let Some(fields) = value.as_object() else {
+                report(Severity::Error, "snippet must be an object".into());
+                continue;
+            };
+            let violations = schema_violations(fields);
+            let schema_valid = violations.is_empty();
+            for violation in violations {
+                report(Severity::Error, violation);
+            }
+            if !schema_valid {
+                continue;
+            }
+
+            let snippet: Snippet = serde_json::from_value(value.clone())?;
+
+            if snippet.note.trim().is_empty() {
+                report(Severity::Warning, "empty note".into());
+            }
+
+            let extracted =
+                match PlaceHolder::extract_placeholders(&snippet.content, &snippet.sources) {
+                    Ok(extracted) => extracted,
+                    Err(e) => {
+                        report(Severity::Error, e.to_string());
+                        continue;
+                    }
+                };
+
+            if serde_json::to_value(&snippet.placeholders).ok()
+                != serde_json::to_value(&extracted).ok()
+            {
+                report(
+                    Severity::Error,
+                    format!(
+                        "stored placeholders do not match the content, expected {}",
+                        serde_json::to_string(&extracted)?
+                    ),
+                );
+            }
+
+            for placeholder in &extracted {
+                if let PlaceHolder::Unknown(name) = placeholder {
+                    report(
+                        Severity::Error,
+                        format!(
+                            "`<{}>` has no provider; add a source or an inline command",
+                            name
+                        ),
+                    );
+                }
+            }
+
+            if shellcheck {
+                match run_shellcheck(&snippet) {
+                    Ok(findings) => {
+                        for finding in findings {
+                            report(Severity::Warning, finding);
+                        }
+                    }
+                    Err(e) => report(Severity::Error, format!("shellcheck: {}", e)),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Type errors and unexpected fields of one snippet object.
+fn schema_violations(fields: &Map<String, Value>) -> Vec<String> {
+    let mut violations = Vec::new();
+
+    for required in ["name", "content", "note"] {
+        match fields.get(required) {
+            Some(Value::String(_)) => {}
+            Some(_) => violations.push(format!("`{}` must be a string", required)),
+            None => violations.push(format!("missing `{}`", required)),
+        }
+    }
+
+    for (field, value) in fields {
+        let valid = match field.as_str() {
+            "name" | "content" | "note" => true,
+            "placeholders" => value.is_array(),
+            "sources" => value
+                .as_object()
+                .is_some_and(|sources| sources.values().all(Value::is_string)),
+            "danger" => matches!(value.as_str(), Some("low" | "high")),
+            "requires" => value
+                .as_array()
+                .is_some_and(|tools| tools.iter().all(Value::is_string)),
+            _ => {
+                violations.push(format!(
+                    "unknown field `{}`, expected one of {}",
+                    field,
+                    FIELDS.join(", ")
+                ));
+                continue;
+            }
+        };
+
+        if !valid {
+            let expected = match field.as_str() {
+                "placeholders" => "an array",
+                "sources" => "an object of provider commands",
+                "danger" => "`low` or `high`",
+                _ => "an array of program names",
+            };
+            violations.push(format!("`{}` must be {}", field, expected));
+        }
+    }
+
+    violations
+}
+
+/// Runs shellcheck over the command with every placeholder replaced by its name.
+fn run_shellcheck(snippet: &Snippet) -> Result<Vec<String>, SnipsterError> {
+    let command = PlaceHolder::replace_with_names(snippet)?;
+
+    let mut child = Command::new("shellcheck")
+        .args(["--shell=bash", "--format=gcc", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(command.as_bytes())?;
+    let output = child.wait_with_output()?;
+
+    // `-:1:6: warning: Double quote to prevent globbing and word splitting. [SC2086]`
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            let finding = line.splitn(4, ':').nth(3).unwrap_or(line).trim();
+            format!("shellcheck: {}", finding)
+        })
+        .collect())
+}
+
+fn category_pattern(category: &str) -> String {
+    format!(
+        r#"{}\s*:"#,
+        regex::escape(&serde_json::to_string(category).unwrap_or_default())
+    )
+}
+
+/// Line of the `occurrence`th snippet named `name` under `category`.
+fn snippet_line(contents: &str, category: &str, name: &str, occurrence: usize) -> Option<usize> {
+    let start = Regex::new(&category_pattern(category))
+        .ok()?
+        .find(contents)?
+        .end();
+    let pattern = format!(
+        r#""name"\s*:\s*{}"#,
+        regex::escape(&serde_json::to_string(name).ok()?)
+    );
+
+    let found = Regex::new(&pattern)
+        .ok()?
+        .find_iter(&contents[start..])
+        .nth(occurrence)?;
+
+    Some(line_at(contents, start + found.start()))
+}
+
+fn locate(contents: &str, pattern: &str) -> Option<usize> {
+    let found = Regex::new(pattern).ok()?.find(contents)?;
+
+    Some(line_at(contents, found.start()))
+}
+
+fn line_at(contents: &str, offset: usize) -> usize {
+    contents[..offset].matches('\n').count() + 1
+}
@@ -1,14 +1,20 @@
-use std::process::{Command, Stdio};
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, ExitStatus, Stdio};
 
 use crate::{
+    config::{load_config, Config},
     error::SnipsterError,
+    providers::Row,
     storage::{
-        file::{write_snippet, Snippet, Snipster},
-        placeholder::PlaceHolder,
+        file::{load_snippets, write_snippet, Danger, Snippet, Snipster},
+        placeholder::{PlaceHolder, Prompt},
+        shell::Shell,
+        template::Template,
     },
 };
 
-use super::fzf_builder::FzfBuilder;
+use super::{form::PlaceholderForm, fzf_builder::FzfBuilder};
 
 pub struct SnipsterCommand;
 
@@ -22,45 +28,46 @@ impl SnipsterCommand {
         if let Some(command) = command {
             full_command = format!(r#"{} | {}"#, command, fzf.build());
         } else {
-            full_command = format!("{}", fzf.build());
+            full_command = fzf.build();
         }
 
         // dbg!("{}", &full_command);
 
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(full_command)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .map_err(|e| {
-                SnipsterError::OutputParsingError(format!("Failed to execute script: {}", e))
-            })?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let hint = if stderr.contains("fzf: command not found")
-                || stderr.contains("jq: command not found")
-            {
-                Some("Ensure fzf and jq are installed.")
-            } else {
-                None
-            };
-
-            let hint_message = hint.map_or("".to_string(), |hint| format!("Hint: {}", hint));
-            return Err(SnipsterError::CommandError(format!(
-                "{} {}",
-                stderr.trim(),
-                hint_message
-            )));
-        }
+        run_fzf(&full_command, None, &HashMap::new())
+    }
 
-        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    /// Picks from rows of a built-in provider: fzf shows their display text and
+    /// returns the value of the picked rows, one per line. The values resolved so far
+    /// are exported to previews as `SNIPSTER_<NAME>`.
+    pub fn fzf_with_rows(
+        fzf: FzfBuilder,
+        rows: &[Row],
+        resolved: &HashMap<String, String>,
+    ) -> Result<String, SnipsterError> {
+        let input: String = rows
+            .iter()
+            .map(|row| format!("{}\t{}\n", row.value, row.display))
+            .collect();
+        let fzf = fzf.delimiter(r"'\t'").with_nth("2..").accept("{1}");
 
-        Ok(stdout)
+        run_fzf(&fzf.build(), Some(&input), resolved)
     }
 
     pub fn get_snip_with_fzf() -> Result<Snipster, SnipsterError> {
+        let config = load_config()?;
+
+        // Snippets whose tools are missing, as `category/name` -> comma separated tools.
+        let mut unavailable = HashMap::new();
+        for (category, snippets) in load_snippets()? {
+            for snippet in snippets {
+                let missing = snippet.missing_requirements();
+                if !missing.is_empty() {
+                    unavailable
+                        .insert(format!("{}/{}", category, snippet.name), missing.join(", "));
+                }
+            }
+        }
+
         // Uhm yes, this is kinda messy.
         let command = r#"
     max_category_len=$(jq -r 'to_entries | .[] | .key | length' snippets.json | sort -nr | head -n 1)
@@ -68,9 +75,13 @@ impl SnipsterCommand {
     max_note_len=$(jq -r '.[] | .[] | .note | length' snippets.json | sort -nr | head -n 1)
 
     cat snippets.json | \
-    jq -r 'to_entries | .[] | .key as $category | .value[] | "\($category)\t\(.name)\t\(.note)\t\(.content)\t\u0001\(. | @json)"' | \
+    jq -r --argjson missing "$SNIPSTER_MISSING" --argjson hide "$SNIPSTER_HIDE" 'to_entries | .[] | .key as $category | .value[] | ($missing[$category + "/" + .name] // "") as $needs | select(($hide | not) or $needs == "") | "\($category)\t\(.name)\t\(.note)\t\(.content)\t\u0001\(. | @json)\t\($needs)"' | \
     awk -F '\t' -v max_category_len="$max_category_len" -v max_name_len="$max_name_len" -v max_note_len="$max_note_len" '{
-        printf "\033[35m%-*s\033[0m\t\033[32m%-*s\033[0m\t\033[36m%-*s\033[0m\t\033[33m%s\033[0m\t%s\n", max_category_len, $1, max_name_len, $2, max_note_len, $3, $4, $5;
+        if ($6 != "") {
+            printf "\033[2m%-*s\t%-*s\t%-*s\t%s\033[0m\t%s\n", max_category_len, $1, max_name_len, $2, max_note_len, $3 " (needs " $6 ")", $4, $5;
+        } else {
+            printf "\033[35m%-*s\033[0m\t\033[32m%-*s\033[0m\t\033[36m%-*s\033[0m\t\033[33m%s\033[0m\t%s\n", max_category_len, $1, max_name_len, $2, max_note_len, $3, $4, $5;
+        }
     }' | \
     fzf --ansi --reverse --delimiter="\t" \
         --with-nth=1,2,3 \
@@ -79,9 +90,12 @@ impl SnipsterCommand {
         --preview-window=up:4:wrap \
         --bind='enter:become(echo {5})'"#;
 
+        // Handed over in the environment so the values never become script text.
         let output = Command::new("sh")
             .arg("-c")
             .arg(command)
+            .env("SNIPSTER_MISSING", serde_json::to_string(&unavailable)?)
+            .env("SNIPSTER_HIDE", config.hide_unavailable.to_string())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .output()
@@ -89,12 +103,16 @@ impl SnipsterCommand {
                 SnipsterError::OutputParsingError(format!("Failed to execute script: {}", e))
             })?;
 
+        if is_cancelled(&output.status) {
+            return Err(SnipsterError::Cancelled);
+        }
+
         let stdout = String::from_utf8(output.stdout)?;
 
         let unescaped_stdout = stdout.trim().trim_start_matches('\u{1}');
 
-        let snippet: Snippet = serde_json::from_str(unescaped_stdout.trim())
-            .map_err(|e| SnipsterError::SerdeError(e))?;
+        let snippet: Snippet =
+            serde_json::from_str(unescaped_stdout.trim()).map_err(SnipsterError::SerdeError)?;
 
         Ok(Snipster {
             snippet: Some(snippet),
@@ -106,15 +124,31 @@ impl SnipsterCommand {
         name: &str,
         content: &str,
         note: &str,
+        sources: &[String],
+        danger: Option<Danger>,
+        requires: &[String],
     ) -> Result<Snipster, SnipsterError> {
-        let placeholders =
-            PlaceHolder::extract_placeholders(content).map_err(SnipsterError::PlaceHolderError)?;
+        let sources = sources
+            .iter()
+            .map(|source| match source.split_once('=') {
+                Some((name, command)) => Ok((name.trim().to_string(), command.trim().to_string())),
+                None => Err(SnipsterError::CommandError(format!(
+                    "Invalid source `{}`, expected `name=command`",
+                    source
+                ))),
+            })
+            .collect::<Result<HashMap<String, String>, SnipsterError>>()?;
+
+        let placeholders = PlaceHolder::extract_placeholders(content, &sources)?;
 
         let snip: Snippet = Snippet {
             name: name.to_string(),
             content: content.to_string(),
             note: note.to_string(),
             placeholders,
+            sources,
+            danger,
+            requires: requires.to_vec(),
         };
 
         dbg!("{}", &category);
@@ -126,18 +160,156 @@ impl SnipsterCommand {
         })
     }
 
-    pub fn edit_command_with_input(content: &str) -> Result<Vec<String>, SnipsterError> {
-        let placeholders =
-            PlaceHolder::extract_placeholders(content).map_err(SnipsterError::PlaceHolderError);
+    pub fn edit_command_with_input(snippet: &Snippet) -> Result<Vec<String>, SnipsterError> {
+        let template = Template::parse(&snippet.content)?.with_sources(&snippet.sources);
+        let names = template.names();
+        let config = load_config()?;
+        let mut resolved: HashMap<String, String> = HashMap::new();
 
-        match placeholders {
-            Ok(result) => result
-                .iter()
-                .map(|x| x.handle())
-                .collect::<Result<Vec<String>, SnipsterError>>(),
-            Err(_) => Err(SnipsterError::CommandError(
-                "Failed to parse fzf command".to_string(),
+        // Providers may refer to other placeholders, so resolve in dependency order.
+        // Dependencies that do not occur in the content are still asked for.
+        let order = PlaceHolder::resolution_order(&template)?;
+        let total = order.len();
+        let mut step = 0;
+
+        while step < total {
+            let name = &order[step];
+            let placeholder = PlaceHolder::from_template(&template, name);
+            let prompt = Self::prompt_for(&template, &config, &resolved, name, step, total);
+
+            match placeholder.handle(&resolved, &prompt) {
+                Ok(value) => {
+                    resolved.insert(name.clone(), value);
+                    step += 1;
+                }
+                // Esc goes back to the previous placeholder, or out of the snippet
+                // when this is the first one.
+                Err(SnipsterError::Cancelled) if step > 0 => {
+                    step -= 1;
+                    resolved.remove(&order[step]);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(names
+            .iter()
+            .map(|name| resolved.remove(*name).unwrap_or_default())
+            .collect())
+    }
+
+    /// Executes `command` with the user's shell, attached to the terminal.
+    pub fn run_command(command: &str) -> Result<Snipster, SnipsterError> {
+        // The shell the placeholder values were quoted for.
+        let shell = Shell::detect();
+
+        let status = Command::new(shell.program())
+            .arg("-c")
+            .arg(command)
+            .status()?;
+
+        if !status.success() {
+            return Err(SnipsterError::CommandError(format!(
+                "`{}` exited with {}",
+                command, status
+            )));
+        }
+
+        Ok(Snipster { snippet: None })
+    }
+
+    pub fn edit_command_with_form(snippet: &Snippet) -> Result<Vec<String>, SnipsterError> {
+        PlaceholderForm::new(snippet)?.run()
+    }
+
+    pub(crate) fn prompt_for(
+        template: &Template,
+        config: &Config,
+        resolved: &HashMap<String, String>,
+        name: &str,
+        step: usize,
+        total: usize,
+    ) -> Prompt {
+        Prompt {
+            multi: template.is_multi(name),
+            preview: config.previews.get(name).cloned(),
+            header: Some(format!(
+                "[{}/{}] {}: {}",
+                step + 1,
+                total,
+                name,
+                template.outline(resolved, name)
             )),
+            options: template.options(name),
         }
     }
 }
+
+/// Runs an fzf pipeline with `sh`, feeding it `input` when given, and returns what
+/// it prints.
+fn run_fzf(
+    command: &str,
+    input: Option<&str>,
+    resolved: &HashMap<String, String>,
+) -> Result<String, SnipsterError> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(
+            resolved
+                .iter()
+                .map(|(name, value)| (format!("SNIPSTER_{}", name.to_uppercase()), value)),
+        )
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            SnipsterError::OutputParsingError(format!("Failed to execute script: {}", e))
+        })?;
+
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        // fzf may quit before reading everything; that is not an error here.
+        let _ = stdin.write_all(input.as_bytes());
+    }
+
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        // A provider that is not installed leaves an empty list the user can only
+        // escape from, so report it rather than treating it as a cancel.
+        if is_cancelled(&output.status) && !stderr.contains("not found") {
+            return Err(SnipsterError::Cancelled);
+        }
+
+        let hint = if stderr.contains("fzf: command not found")
+            || stderr.contains("jq: command not found")
+        {
+            Some("Ensure fzf and jq are installed.")
+        } else {
+            None
+        };
+
+        let hint_message = hint.map_or("".to_string(), |hint| format!("Hint: {}", hint));
+        return Err(SnipsterError::CommandError(format!(
+            "{} {}",
+            stderr.trim(),
+            hint_message
+        )));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    Ok(stdout)
+}
+
+// fzf exits with 1 when nothing matched and 130 when it is aborted with Esc or Ctrl-C.
+fn is_cancelled(status: &ExitStatus) -> bool {
+    matches!(status.code(), Some(1 | 130))
+}
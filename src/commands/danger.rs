@@ -0,0 +1,195 @@
+use regex::Regex;
+use std::io::{self, BufRead, Write};
+
+use crate::{
+    config::Config,
+    error::SnipsterError,
+    storage::file::{Danger, Snippet},
+};
+
+// Built-in rules as (description, pattern). Options may come anywhere after the
+// command word, so `(?:[^;&|\n]*\s)?` skips the other arguments of the same
+// command without running into the next one.
+const RULES: &[(&str, &str)] = &[
+    (
+        "recursive forced delete (rm -rf)",
+        r"\brm\s(?:[^;&|\n]*\s)?(?:-[a-zA-Z]*(?:[rR][a-zA-Z]*f|f[a-zA-Z]*[rR])|(?:-[a-zA-Z]*[rR][a-zA-Z]*|--recursive)\s(?:[^;&|\n]*\s)?(?:-[a-zA-Z]*f[a-zA-Z]*|--force)|(?:-[a-zA-Z]*f[a-zA-Z]*|--force)\s(?:[^;&|\n]*\s)?(?:-[a-zA-Z]*[rR][a-zA-Z]*|--recursive))\b",
+    ),
+    (
+        "raw write to a device or file (dd of=)",
+        r"\bdd\s[^;&|\n]*\bof=",
+    ),
+    ("filesystem creation (mkfs)", r"\bmkfs(?:\.\w+)?\b"),
+    (
+        "killing init or every process (kill 1 / kill -1)",
+        r"\bkill\s(?:[^;&|\n]*\s)?-?1(?:[\s;&|]|$)",
+    ),
+    (
+        "recursive world-writable permissions (chmod -R 777)",
+        r"\bchmod\s(?:[^;&|\n]*\s)?(?:(?:-[a-zA-Z]*R[a-zA-Z]*|--recursive)\s(?:[^;&|\n]*\s)?0?777|0?777\s(?:[^;&|\n]*\s)?(?:-[a-zA-Z]*R[a-zA-Z]*|--recursive))\b",
+    ),
+    (
+        "git force push",
+        r"\bgit\s+push\b[^;&|\n]*(?:\s-[a-zA-Z]*f\b|\s--force\b|\s\+\S)",
+    ),
+    (
+        "redirect onto a block device",
+        r">\s*/dev/(?:sd|hd|vd|nvme|mmcblk)\w*",
+    ),
+];
+
+/// Describes every rule `command` matches, built-in ones first, then the
+/// `danger_patterns` from the config.
+pub fn find_dangers(command: &str, config: &Config) -> Result<Vec<String>, SnipsterError> {
+    let mut matched = Vec::new();
+
+    for (description, pattern) in RULES {
+        let rule = Regex::new(pattern).expect("valid built-in danger rule");
+        if rule.is_match(command) {
+            matched.push(description.to_string());
+        }
+    }
+
+    for pattern in &config.danger_patterns {
+        let rule = Regex::new(pattern).map_err(|e| {
+            SnipsterError::CommandError(format!("Invalid danger pattern `{}`: {}", pattern, e))
+        })?;
+        if rule.is_match(command) {
+            matched.push(format!("configured pattern `{}`", pattern));
+        }
+    }
+
+    Ok(matched)
+}
+
+/// Warns about a destructive command and asks the user to type `yes`. Anything else
+/// cancels. Commands that match no rule, in snippets not marked `danger: high`, pass.
+pub fn confirm_if_dangerous(
+    snippet: &Snippet,
+    command: &str,
+    config: &Config,
+) -> Result<(), SnipsterError> {
+    let mut reasons = find_dangers(command, config)?;
+    if let Some(Danger::High) = snippet.danger {
+        reasons.insert(0, "snippet is marked `danger: high`".to_string());
+    }
+
+    if reasons.is_empty() {
+        return Ok(());
+    }
+
+    // Prompted on stderr so `write --print` keeps stdout for the command.
+    let mut stderr = io::stderr();
+    writeln!(
+        stderr,
+        "\x1b[91mwarning:\x1b[0m this command looks destructive"
+    )?;
+    writeln!(stderr, "  \x1b[33m{}\x1b[0m", command)?;
+    for reason in &reasons {
+        writeln!(stderr, "  \x1b[91m- {}\x1b[0m", reason)?;
+    }
+    write!(stderr, "Type \x1b[1myes\x1b[0m to continue: ")?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    if answer.trim() == "yes" {
+        Ok(())
+    } else {
+        Err(SnipsterError::Cancelled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dangers(command: &str) -> Vec<String> {
+        find_dangers(command, &Config::default()).unwrap()
+    }
+
+    fn assert_rule(rule: &str, commands: &[&str]) {
+        for command in commands {
+            assert!(
+                dangers(command).iter().any(|danger| danger.contains(rule)),
+                "`{}` should match {}",
+                command,
+                rule
+            );
+        }
+    }
+
+    #[test]
+    fn rules_match_options_anywhere() {
+        assert_rule(
+            "rm -rf",
+            &[
+                "rm -rf /",
+                "rm x -rf",
+                "rm -r x -f",
+                "rm --force a --recursive",
+                "rm -fR x",
+            ],
+        );
+        assert_rule("dd of=", &["dd if=/dev/zero of=/dev/sda bs=1M"]);
+        assert_rule("mkfs", &["mkfs.ext4 /dev/sdb1", "sudo mkfs /dev/sdb"]);
+        assert_rule("kill 1", &["kill -9 1", "kill -1", "kill 1; echo done"]);
+        assert_rule(
+            "chmod -R 777",
+            &[
+                "chmod -R 777 /",
+                "chmod 777 -R /",
+                "chmod 0777 / --recursive",
+            ],
+        );
+        assert_rule(
+            "force push",
+            &[
+                "git push -f",
+                "git push origin main --force",
+                "git push origin +main",
+            ],
+        );
+        assert_rule("block device", &["cat image.iso > /dev/sdb"]);
+    }
+
+    #[test]
+    fn harmless_commands_pass() {
+        for command in [
+            "rm -r x",
+            "rm -f x",
+            "rm -r x; touch -f y",
+            "kill 1234",
+            "kill -9 1234",
+            "chmod 777 file",
+            "chmod -R 755 dir",
+            "git push origin main",
+            "git push && ls -f",
+        ] {
+            assert_eq!(dangers(command), Vec::<String>::new(), "`{}`", command);
+        }
+    }
+
+    #[test]
+    fn configured_patterns_are_checked() {
+        let config = Config {
+            danger_patterns: vec![r"\bdrop\s+table\b".to_string()],
+            ..Config::default()
+        };
+
+        assert_eq!(
+            find_dangers("psql -c 'drop table users'", &config).unwrap(),
+            vec![r"configured pattern `\bdrop\s+table\b`"]
+        );
+    }
+
+    #[test]
+    fn invalid_configured_patterns_are_errors() {
+        let config = Config {
+            danger_patterns: vec!["(".to_string()],
+            ..Config::default()
+        };
+        assert!(find_dangers("ls", &config).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use clipboard::{ClipboardContext, ClipboardProvider};
+
+use crate::{
+    error::SnipsterError,
+    storage::{
+        file::{load_snippets, Snipster, SNIPPET_LOCATION},
+        placeholder::{PlaceHolder, BUILT_IN_NAMES},
+        requirements::is_installed,
+    },
+};
+
+// The snippet picker binds `become`, which fzf gained in 0.38.
+const MIN_FZF_VERSION: (u32, u32) = (0, 38);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Serialize, Debug)]
+struct Check {
+    name: String,
+    status: Status,
+    detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, status: Status, detail: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// Checks the tools, clipboard and snippet file snipster depends on and prints a
+/// pass/warn/fail report, as JSON with `json`. Fails when any check does.
+pub fn doctor(json: bool) -> Result<Snipster, SnipsterError> {
+    let mut checks = vec![check_fzf(), check_jq(), check_clipboard()];
+    checks.extend(check_snippets());
+    checks.extend(check_providers());
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&checks)?);
+    } else {
+        print_report(&checks);
+    }
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    if failed > 0 {
+        return Err(SnipsterError::ChecksFailed(failed));
+    }
+
+    Ok(Snipster { snippet: None })
+}
+
+fn print_report(checks: &[Check]) {
+    for check in checks {
+        let label = match check.status {
+            Status::Pass => "\x1b[32mpass\x1b[0m",
+            Status::Warn => "\x1b[33mwarn\x1b[0m",
+            Status::Fail => "\x1b[91mfail\x1b[0m",
+        };
+        println!("[{}] \x1b[1m{}\x1b[0m: {}", label, check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            println!("       \x1b[2mfix: {}\x1b[0m", fix);
+        }
+    }
+}
+
+/// First line of `tool --version`, or `None` when it cannot be run.
+fn version(tool: &str) -> Option<String> {
+    let output = Command::new(tool).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().next().map(|line| line.trim().to_string())
+}
+
+fn check_fzf() -> Check {
+    let Some(version) = version("fzf") else {
+        return Check::new("fzf", Status::Fail, "not installed")
+            .fix("Install fzf 0.38 or newer, e.g. `apt install fzf` or `brew install fzf`");
+    };
+
+    // `0.44.1 (debian)`
+    let mut numbers = version
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|part| part.parse::<u32>().ok());
+    let release = (numbers.next().unwrap_or(0), numbers.next().unwrap_or(0));
+
+    if release < MIN_FZF_VERSION {
+        Check::new(
+            "fzf",
+            Status::Fail,
+            format!("{} is too old for the snippet picker", version),
+        )
+        .fix("Upgrade fzf to 0.38 or newer")
+    } else {
+        Check::new("fzf", Status::Pass, version)
+    }
+}
+
+fn check_jq() -> Check {
+    match version("jq") {
+        Some(version) => Check::new("jq", Status::Pass, version),
+        None => Check::new("jq", Status::Fail, "not installed")
+            .fix("Install jq, e.g. `apt install jq` or `brew install jq`"),
+    }
+}
+
+fn check_clipboard() -> Check {
+    let context: Result<ClipboardContext, _> = ClipboardProvider::new();
+
+    match context {
+        Ok(_) => Check::new("clipboard", Status::Pass, "available"),
+        Err(e) => Check::new("clipboard", Status::Warn, format!("unavailable: {}", e))
+            .fix("Run inside a graphical session, or use `snipster write --print`"),
+    }
+}
+
+/// The snippet file, and for every snippet whether its stored `placeholders`
+/// still match its content.
+fn check_snippets() -> Vec<Check> {
+    if !Path::new(SNIPPET_LOCATION).exists() {
+        return vec![Check::new(
+            "snippet file",
+            Status::Warn,
+            format!("{} not found", SNIPPET_LOCATION),
+        )
+        .fix("Add a snippet with `snipster add`")];
+    }
+
+    let snippets = match load_snippets() {
+        Ok(snippets) => snippets,
+        Err(e) => {
+            return vec![Check::new(
+                "snippet file",
+                Status::Fail,
+                format!("{} does not parse: {}", SNIPPET_LOCATION, e),
+            )
+            .fix(format!("Fix the JSON in {}", SNIPPET_LOCATION))]
+        }
+    };
+
+    let count: usize = snippets.values().map(Vec::len).sum();
+    let mut checks = vec![Check::new(
+        "snippet file",
+        Status::Pass,
+        format!("{} snippets in {}", count, SNIPPET_LOCATION),
+    )];
+
+    let mut categories: Vec<_> = snippets.into_iter().collect();
+    categories.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (category, snippets) in categories {
+        for snippet in snippets {
+            let name = format!("{}/{}", category, snippet.name);
+
+            let extracted =
+                match PlaceHolder::extract_placeholders(&snippet.content, &snippet.sources) {
+                    Ok(extracted) => extracted,
+                    Err(e) => {
+                        checks.push(
+                            Check::new(name, Status::Fail, e.to_string())
+                                .fix("Fix the template in its `content`"),
+                        );
+                        continue;
+                    }
+                };
+
+            let stored = serde_json::to_value(&snippet.placeholders).ok();
+            if stored != serde_json::to_value(&extracted).ok() {
+                let expected = serde_json::to_string(&extracted).unwrap_or_default();
+                checks.push(
+                    Check::new(
+                        name.clone(),
+                        Status::Warn,
+                        "stored placeholders do not match its content",
+                    )
+                    .fix(format!("Set its `placeholders` to {}", expected)),
+                );
+            }
+
+            let missing = snippet.missing_requirements();
+            if !missing.is_empty() {
+                checks.push(
+                    Check::new(
+                        name,
+                        Status::Warn,
+                        format!("needs {}, which is not installed", missing.join(", ")),
+                    )
+                    .fix(format!("Install {}", missing.join(", "))),
+                );
+            }
+        }
+    }
+
+    checks
+}
+
+/// Whether the command behind every built-in provider is installed.
+fn check_providers() -> Vec<Check> {
+    let mut tools: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for name in BUILT_IN_NAMES {
+        for tool in PlaceHolder::from_string(name).requires() {
+            tools.entry(tool).or_default().push(format!("<{}>", name));
+        }
+    }
+
+    tools
+        .into_iter()
+        .map(|(tool, users)| {
+            let check_name = format!("provider {}", tool);
+            if is_installed(&tool) {
+                Check::new(
+                    check_name,
+                    Status::Pass,
+                    format!("used by {}", users.join(" ")),
+                )
+            } else {
+                Check::new(
+                    check_name,
+                    Status::Warn,
+                    format!("not installed, {} cannot be picked", users.join(" ")),
+                )
+                .fix(format!("Install {} to use these placeholders", tool))
+            }
+        })
+        .collect()
+}
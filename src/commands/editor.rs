@@ -0,0 +1,188 @@
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    style::Print,
+    terminal::{self, Clear, ClearType},
+};
+use std::io::{self, Write};
+
+use crate::{
+    error::SnipsterError,
+    storage::history::{append_history, load_history},
+};
+
+const PROMPT: &str = "\x1b[33m❯\x1b[0m ";
+const PROMPT_WIDTH: usize = 2;
+
+/// A single-line editor for tweaking the resolved command before it is used.
+///
+/// Emacs-style keys: Ctrl-A/E or Home/End, Ctrl-B/F or arrows, Alt-B/F or
+/// Ctrl-arrows by word, Ctrl-W and Alt-Backspace delete the previous word, Alt-D the
+/// next one, Ctrl-U/K kill to the start/end, Up/Down walk the history.
+pub struct LineEditor {
+    buffer: Vec<char>,
+    cursor: usize,
+    history: Vec<String>,
+    index: usize,     // position in `history`, `history.len()` is the edited line
+    draft: Vec<char>, // the edited line while browsing history
+}
+
+impl LineEditor {
+    pub fn new(initial: &str) -> Result<Self, SnipsterError> {
+        let history = load_history()?;
+        let buffer: Vec<char> = initial.chars().collect();
+
+        Ok(LineEditor {
+            cursor: buffer.len(),
+            index: history.len(),
+            draft: buffer.clone(),
+            buffer,
+            history,
+        })
+    }
+
+    /// Returns the edited command, or `SnipsterError::Cancelled` on Esc or Ctrl-C.
+    pub fn run(mut self) -> Result<String, SnipsterError> {
+        // Drawn on stderr so `write --print --edit` keeps stdout for the command.
+        let mut stderr = io::stderr();
+        terminal::enable_raw_mode()?;
+        let result = self.read_line(&mut stderr);
+        terminal::disable_raw_mode()?;
+        eprintln!();
+
+        let command = result?;
+        append_history(&command)?;
+
+        Ok(command)
+    }
+
+    fn read_line(&mut self, stderr: &mut io::Stderr) -> Result<String, SnipsterError> {
+        loop {
+            self.draw(stderr)?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match (key.code, key.modifiers) {
+                (KeyCode::Enter, _) => return Ok(self.buffer.iter().collect()),
+                (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                    return Err(SnipsterError::Cancelled)
+                }
+                (KeyCode::Up, _) => self.browse(-1),
+                (KeyCode::Down, _) => self.browse(1),
+                _ => self.edit(key),
+            }
+        }
+    }
+
+    fn edit(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+        match key.code {
+            KeyCode::Left if ctrl => self.cursor = self.word_start(),
+            KeyCode::Right if ctrl => self.cursor = self.word_end(),
+            KeyCode::Char('b') if alt => self.cursor = self.word_start(),
+            KeyCode::Char('f') if alt => self.cursor = self.word_end(),
+            KeyCode::Char('d') if alt => {
+                let end = self.word_end();
+                self.buffer.drain(self.cursor..end);
+            }
+            KeyCode::Backspace if alt => self.delete_word_before(),
+            KeyCode::Char('w') if ctrl => self.delete_word_before(),
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.buffer.len(),
+            KeyCode::Char('b') if ctrl => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Char('f') if ctrl => self.cursor = (self.cursor + 1).min(self.buffer.len()),
+            KeyCode::Char('u') if ctrl => {
+                self.buffer.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            KeyCode::Char('k') if ctrl => self.buffer.truncate(self.cursor),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.buffer.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.buffer.len(),
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.buffer.remove(self.cursor);
+            }
+            KeyCode::Delete if self.cursor < self.buffer.len() => {
+                self.buffer.remove(self.cursor);
+            }
+            KeyCode::Char(c) if !ctrl && !alt => {
+                self.buffer.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn browse(&mut self, step: isize) {
+        let Some(index) = self.index.checked_add_signed(step) else {
+            return;
+        };
+        if index > self.history.len() {
+            return;
+        }
+
+        if self.index == self.history.len() {
+            self.draft = self.buffer.clone();
+        }
+        self.index = index;
+        self.buffer = match self.history.get(index) {
+            Some(entry) => entry.chars().collect(),
+            None => self.draft.clone(),
+        };
+        self.cursor = self.buffer.len();
+    }
+
+    fn delete_word_before(&mut self) {
+        let start = self.word_start();
+        self.buffer.drain(start..self.cursor);
+        self.cursor = start;
+    }
+
+    fn word_start(&self) -> usize {
+        let mut i = self.cursor;
+        while i > 0 && self.buffer[i - 1].is_whitespace() {
+            i -= 1;
+        }
+        while i > 0 && !self.buffer[i - 1].is_whitespace() {
+            i -= 1;
+        }
+        i
+    }
+
+    fn word_end(&self) -> usize {
+        let mut i = self.cursor;
+        while i < self.buffer.len() && self.buffer[i].is_whitespace() {
+            i += 1;
+        }
+        while i < self.buffer.len() && !self.buffer[i].is_whitespace() {
+            i += 1;
+        }
+        i
+    }
+
+    fn draw(&self, stderr: &mut io::Stderr) -> Result<(), SnipsterError> {
+        let line: String = self.buffer.iter().collect();
+
+        queue!(
+            stderr,
+            cursor::MoveToColumn(0),
+            Clear(ClearType::CurrentLine),
+            Print(PROMPT),
+            Print(line),
+            cursor::MoveToColumn((PROMPT_WIDTH + self.cursor) as u16)
+        )?;
+        stderr.flush()?;
+
+        Ok(())
+    }
+}
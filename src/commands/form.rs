@@ -0,0 +1,264 @@
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::Print,
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::collections::HashMap;
+use std::io::{self, Stderr, Write};
+
+use crate::{
+    config::{load_config, Config},
+    error::SnipsterError,
+    storage::{file::Snippet, placeholder::PlaceHolder, template::Template},
+};
+
+use super::commands::SnipsterCommand;
+
+/// Resolves all placeholders of a snippet on one screen. Every field can be typed
+/// into directly or filled from its provider picker, and the resulting command is
+/// shown as it changes.
+pub struct PlaceholderForm<'a> {
+    snippet: &'a Snippet,
+    template: Template,
+    order: Vec<String>,
+    values: HashMap<String, String>,
+    selected: usize,
+    cursor: usize, // in chars, within the selected field
+}
+
+impl<'a> PlaceholderForm<'a> {
+    pub fn new(snippet: &'a Snippet) -> Result<Self, SnipsterError> {
+        let template = Template::parse(&snippet.content)?.with_sources(&snippet.sources);
+        let order = PlaceHolder::resolution_order(&template)?;
+
+        Ok(PlaceholderForm {
+            snippet,
+            template,
+            order,
+            values: HashMap::new(),
+            selected: 0,
+            cursor: 0,
+        })
+    }
+
+    /// Returns the values for the snippet's placeholders, like
+    /// `SnipsterCommand::edit_command_with_input`.
+    pub fn run(mut self) -> Result<Vec<String>, SnipsterError> {
+        if self.order.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let config = load_config()?;
+        // Drawn on stderr so `write --form --print` keeps stdout for the command.
+        let mut stderr = io::stderr();
+        let _screen = Screen::enter()?;
+
+        loop {
+            self.draw(&mut stderr)?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match (key.code, key.modifiers) {
+                (KeyCode::Esc, _) => return Err(SnipsterError::Cancelled),
+                (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                    return Err(SnipsterError::Cancelled)
+                }
+                (KeyCode::Enter, _) => return Ok(self.values()),
+                (KeyCode::Tab | KeyCode::Down, _) => self.select(self.selected + 1),
+                (KeyCode::BackTab | KeyCode::Up, _) => {
+                    self.select(self.selected + self.order.len() - 1)
+                }
+                (KeyCode::F(2), _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                    self.pick(&config)?
+                }
+                _ => self.edit(key),
+            }
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index % self.order.len();
+        self.cursor = self.current().chars().count();
+    }
+
+    fn current(&self) -> &str {
+        self.values
+            .get(&self.order[self.selected])
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// Opens the provider picker of the selected field, using the other fields as
+    /// the values its command may refer to.
+    fn pick(&mut self, config: &Config) -> Result<(), SnipsterError> {
+        let name = &self.order[self.selected];
+        let resolved: HashMap<String, String> = self
+            .values
+            .iter()
+            .filter(|(key, value)| *key != name && !value.is_empty())
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        let placeholder = PlaceHolder::from_template(&self.template, name);
+        let prompt = SnipsterCommand::prompt_for(
+            &self.template,
+            config,
+            &resolved,
+            name,
+            self.selected,
+            self.order.len(),
+        );
+
+        Screen::suspend()?;
+        let picked = placeholder.handle(&resolved, &prompt);
+        Screen::resume()?;
+
+        match picked {
+            Ok(value) => {
+                self.values.insert(name.clone(), value);
+                self.select(self.selected);
+                Ok(())
+            }
+            Err(SnipsterError::Cancelled) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn edit(&mut self, key: KeyEvent) {
+        let mut chars: Vec<char> = self.current().chars().collect();
+        let cursor = self.cursor.min(chars.len());
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Left, _) => self.cursor = cursor.saturating_sub(1),
+            (KeyCode::Right, _) => self.cursor = (cursor + 1).min(chars.len()),
+            (KeyCode::Home, _) => self.cursor = 0,
+            (KeyCode::End, _) => self.cursor = chars.len(),
+            (KeyCode::Backspace, _) if cursor > 0 => {
+                chars.remove(cursor - 1);
+                self.cursor = cursor - 1;
+            }
+            (KeyCode::Delete, _) if cursor < chars.len() => {
+                chars.remove(cursor);
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                chars.drain(..cursor);
+                self.cursor = 0;
+            }
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                chars.insert(cursor, c);
+                self.cursor = cursor + 1;
+            }
+            _ => return,
+        }
+
+        let name = self.order[self.selected].clone();
+        self.values.insert(name, chars.into_iter().collect());
+    }
+
+    fn values(&self) -> Vec<String> {
+        self.template
+            .names()
+            .iter()
+            .map(|name| self.values.get(*name).cloned().unwrap_or_default())
+            .collect()
+    }
+
+    fn draw(&self, stderr: &mut Stderr) -> Result<(), SnipsterError> {
+        let width = self.order.iter().map(|name| name.len()).max().unwrap_or(0);
+
+        // Filters such as `fmt` can fail on a half-typed value; fall back to the outline.
+        let command =
+            PlaceHolder::replace_with_value(self.snippet, &self.values()).unwrap_or_else(|_| {
+                self.template
+                    .outline(&self.values, &self.order[self.selected])
+            });
+
+        queue!(stderr, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        queue!(
+            stderr,
+            Print(format!(
+                "\x1b[32m{}\x1b[0m  \x1b[36m{}\x1b[0m",
+                self.snippet.name, self.snippet.note
+            ))
+        )?;
+
+        for (i, name) in self.order.iter().enumerate() {
+            let marker = if i == self.selected {
+                "\x1b[33m›\x1b[0m"
+            } else {
+                " "
+            };
+            let value = self
+                .values
+                .get(name)
+                .map(String::as_str)
+                .unwrap_or_default();
+            queue!(
+                stderr,
+                cursor::MoveTo(0, i as u16 + 2),
+                Print(format!(
+                    "{} \x1b[35m{:<width$}\x1b[0m : {}",
+                    marker,
+                    name,
+                    value.replace('\n', " "),
+                    width = width
+                ))
+            )?;
+        }
+
+        let row = self.order.len() as u16 + 3;
+        queue!(
+            stderr,
+            cursor::MoveTo(0, row),
+            Print(format!("Command: \x1b[33m{}\x1b[0m", command.replace('\n', " "))),
+            cursor::MoveTo(0, row + 2),
+            Print(
+                "\x1b[2mTab/Shift-Tab move · type to edit · Ctrl-P/F2 pick · Enter accept · Esc back\x1b[0m"
+            ),
+            cursor::MoveTo(
+                (width + 5 + self.cursor.min(self.current().chars().count())) as u16,
+                self.selected as u16 + 2
+            ),
+            cursor::Show
+        )?;
+
+        stderr.flush()?;
+        Ok(())
+    }
+}
+
+/// Raw mode on the alternate screen for the lifetime of the form.
+struct Screen;
+
+impl Screen {
+    fn enter() -> Result<Self, SnipsterError> {
+        Self::resume()?;
+        Ok(Screen)
+    }
+
+    // Hands the terminal back for a provider picker.
+    fn suspend() -> Result<(), SnipsterError> {
+        terminal::disable_raw_mode()?;
+        execute!(io::stderr(), LeaveAlternateScreen)?;
+        Ok(())
+    }
+
+    fn resume() -> Result<(), SnipsterError> {
+        execute!(io::stderr(), EnterAlternateScreen)?;
+        terminal::enable_raw_mode()?;
+        Ok(())
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = Screen::suspend();
+    }
+}
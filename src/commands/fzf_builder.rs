@@ -1,15 +1,16 @@
+use crate::storage::shell::{QuoteContext, Shell};
+
 #[derive(Debug, Clone)]
 pub struct FzfOptions {
     multi: bool,
     ansi: bool,
     reverse: bool,
     header: Option<u8>,
+    header_text: Option<String>,
     delimiter: Option<String>,
     with_nth: Option<String>,
     preview: Option<String>,
-    preview_window: Option<String>,
-    bind: Option<String>,
-    height: Option<String>,
+    accept: Option<String>,
 }
 
 impl FzfOptions {
@@ -19,12 +20,11 @@ impl FzfOptions {
             ansi: false,
             reverse: false,
             header: None,
+            header_text: None,
             delimiter: None,
             with_nth: None,
             preview: None,
-            preview_window: None,
-            bind: None,
-            height: None,
+            accept: None,
         }
     }
 }
@@ -62,6 +62,12 @@ impl FzfBuilder {
         self
     }
 
+    // Sticky text above the list, unlike `header` which keeps input lines there.
+    pub fn header_text(mut self, text: &str) -> Self {
+        self.options.header_text = Some(text.to_string());
+        self
+    }
+
     pub fn delimiter(mut self, delimiter: &str) -> Self {
         self.options.delimiter = Some(delimiter.to_string());
         self
@@ -77,18 +83,9 @@ impl FzfBuilder {
         self
     }
 
-    pub fn preview_window(mut self, window: &str) -> Self {
-        self.options.preview_window = Some(window.to_string());
-        self
-    }
-
-    pub fn bind(mut self, bind: &str) -> Self {
-        self.options.bind = Some(bind.to_string());
-        self
-    }
-
-    pub fn height(mut self, height: &str) -> Self {
-        self.options.height = Some(height.to_string());
+    // Prints `field` of the selected line on enter, or of every marked line with `multi`.
+    pub fn accept(mut self, field: &str) -> Self {
+        self.options.accept = Some(field.to_string());
         self
     }
 
@@ -108,6 +105,10 @@ impl FzfBuilder {
         if let Some(ref header) = self.options.header {
             command.push_str(&format!(" --header-lines={} ", header));
         }
+        if let Some(ref text) = self.options.header_text {
+            let text = Shell::Sh.quote(text, QuoteContext::Bare);
+            command.push_str(&format!("--header={} ", text));
+        }
         if let Some(ref delimiter) = self.options.delimiter {
             command.push_str(&format!(" --delimiter={} ", delimiter));
         }
@@ -115,16 +116,19 @@ impl FzfBuilder {
             command.push_str(&format!("--with-nth={} ", with_nth));
         }
         if let Some(ref preview) = self.options.preview {
+            let preview = Shell::Sh.quote(preview, QuoteContext::Bare);
             command.push_str(&format!("--preview={} ", preview));
         }
-        if let Some(ref preview_window) = self.options.preview_window {
-            command.push_str(&format!("--preview-window={} ", preview_window));
-        }
-        if let Some(ref bind) = self.options.bind {
-            command.push_str(&format!("--bind='{}' ", bind));
-        }
-        if let Some(ref height) = self.options.height {
-            command.push_str(&format!("--height={} ", height));
+        if let Some(ref field) = self.options.accept {
+            let accept = if self.options.multi {
+                format!(
+                    r#"enter:become(printf "%s\n" {})"#,
+                    field.replacen('{', "{+", 1)
+                )
+            } else {
+                format!("enter:become(echo {})", field)
+            };
+            command.push_str(&format!("--bind='{}' ", accept));
         }
 
         command
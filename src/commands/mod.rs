@@ -1,2 +1,8 @@
+pub mod check;
+#[allow(clippy::module_inception)]
 pub mod commands;
+pub mod danger;
+pub mod doctor;
+pub mod editor;
+pub mod form;
 pub mod fzf_builder;
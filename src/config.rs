@@ -1 +1,33 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
 
+use crate::error::SnipsterError;
+
+const CONFIG_LOCATION: &str = "./snipster.json";
+
+#[derive(Deserialize, Debug, Default)]
+pub struct Config {
+    /// Preview commands keyed by placeholder name, replacing the provider's own.
+    /// `{}` is the highlighted line, `{1}` its first field.
+    #[serde(default)]
+    pub previews: HashMap<String, String>,
+    /// Extra regexes for commands that need confirmation before they are used.
+    #[serde(default)]
+    pub danger_patterns: Vec<String>,
+    /// Leave snippets whose tools are not installed out of the picker instead of
+    /// greying them out.
+    #[serde(default)]
+    pub hide_unavailable: bool,
+}
+
+pub fn load_config() -> Result<Config, SnipsterError> {
+    if !std::path::Path::new(CONFIG_LOCATION).exists() {
+        return Ok(Config::default());
+    }
+
+    let contents = fs::read_to_string(CONFIG_LOCATION)?;
+    let config = serde_json::from_str(&contents)?;
+
+    Ok(config)
+}
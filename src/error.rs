@@ -1,5 +1,8 @@
 use thiserror::Error;
 
+use crate::storage::template::TemplateError;
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Error)]
 pub enum SnipsterError {
     #[error("Command execution failed: {0}")]
@@ -17,12 +20,18 @@ pub enum SnipsterError {
     #[error("I/O operation failed: {0}")]
     IoError(#[from] std::io::Error),
 
-    #[error("Placeholder parsing failed: {0}")]
+    #[error("Placeholder resolution failed: {0}")]
     PlaceHolderError(String),
 
+    #[error("Invalid snippet template at {0}")]
+    TemplateError(#[from] TemplateError),
+
     #[error("Failed to copy to clipboard: {0}")]
     ClipboardError(String),
 
-    #[error("Failed to write to file.")]
-    WriteError,
+    #[error("{0} check(s) failed")]
+    ChecksFailed(usize),
+
+    #[error("Cancelled")]
+    Cancelled,
 }
@@ -2,15 +2,25 @@ use clipboard::copy_to_clipboard;
 use std::process;
 
 use clap::{Parser, Subcommand};
-use commands::commands::SnipsterCommand;
+use commands::{
+    check::check, commands::SnipsterCommand, danger::confirm_if_dangerous, doctor::doctor,
+    editor::LineEditor,
+};
+use config::load_config;
 use error::SnipsterError;
-use storage::{file::Snipster, placeholder::PlaceHolder};
+use storage::{
+    file::{Danger, Snippet, Snipster, SNIPPET_LOCATION},
+    placeholder::PlaceHolder,
+};
+use tmux::send_to_pane;
 
 mod clipboard;
 mod commands;
 mod config;
 mod error;
+mod providers;
 mod storage;
+mod tmux;
 
 #[derive(Parser)]
 #[command(name = "Snippet Manager")]
@@ -31,49 +41,155 @@ enum Commands {
         content: String,
         #[arg(short = 't', long)]
         note: String,
+        /// Provider command for a placeholder, as `name=command`
+        #[arg(short = 's', long = "source")]
+        sources: Vec<String>,
+        /// Always ask for confirmation before the command is used
+        #[arg(short = 'd', long, value_enum)]
+        danger: Option<Danger>,
+        /// Program the command needs, besides those its placeholders imply
+        #[arg(short = 'r', long = "requires")]
+        requires: Vec<String>,
     },
     List,
-    Write,
+    Write {
+        /// Fill in all placeholders on one form instead of one picker after another
+        #[arg(short = 'f', long)]
+        form: bool,
+        /// Print the command to stdout instead of copying it to the clipboard
+        #[arg(short = 'p', long)]
+        print: bool,
+        /// Edit the resolved command before it is used
+        #[arg(short = 'e', long)]
+        edit: bool,
+        /// Type the command into this tmux pane instead of copying it
+        #[arg(long, value_name = "TARGET", conflicts_with = "print")]
+        tmux_pane: Option<String>,
+    },
+    /// Resolve a snippet and execute it in your shell
+    Run {
+        /// Fill in all placeholders on one form instead of one picker after another
+        #[arg(short = 'f', long)]
+        form: bool,
+        /// Execute the resolved command without editing it first
+        #[arg(long)]
+        no_edit: bool,
+    },
+    /// Lint a snippet library, exiting non-zero when it has errors
+    Check {
+        /// Snippet file to check, the library in use by default
+        file: Option<String>,
+        /// Also run shellcheck over each command with placeholders filled in
+        #[arg(long)]
+        shellcheck: bool,
+    },
+    /// Check the tools, clipboard and snippet file snipster relies on
+    Doctor {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 fn main() {
-    if let Err(e) = run() {
-        eprintln!("\x1b[91m\rerror:\x1b[0m {e}");
-        process::exit(1);
+    match run() {
+        Ok(_) => {}
+        // Quitting a picker is not an error; exit like an interrupted shell command.
+        Err(SnipsterError::Cancelled) => process::exit(130),
+        Err(e) => {
+            eprintln!("\x1b[91m\rerror:\x1b[0m {e}");
+            process::exit(1);
+        }
     }
 }
 
 fn run() -> Result<Snipster, SnipsterError> {
     let cli = Cli::parse();
 
-    let result = match &cli.command {
+    match &cli.command {
         Some(Commands::Add {
             category,
             name,
             content,
             note,
-        }) => SnipsterCommand::add_snip(category, name, content, note),
+            sources,
+            danger,
+            requires,
+        }) => SnipsterCommand::add_snip(category, name, content, note, sources, *danger, requires),
         Some(Commands::List) => SnipsterCommand::get_snip_with_fzf(),
-        Some(Commands::Write) | None => match SnipsterCommand::get_snip_with_fzf() {
-            Ok(snip) => {
-                if let Some(snippet) = snip.snippet {
-                    let output = SnipsterCommand::edit_command_with_input(snippet.content.as_str());
-
-                    let command = match output {
-                        Ok(output) => PlaceHolder::replace_with_value(&snippet, &output),
-                        Err(e) => Err(e),
-                    };
-                    match command {
-                        Ok(cmd) => copy_to_clipboard(&cmd),
-                        Err(e) => Err(e),
-                    }
-                } else {
-                    Err(SnipsterError::CommandError("ERROR".to_string()))
-                }
-            }
-            Err(e) => Err(e),
-        },
-    };
+        Some(Commands::Write {
+            form,
+            print,
+            edit,
+            tmux_pane,
+        }) => write(*form, *print, *edit, tmux_pane.as_deref()),
+        Some(Commands::Run { form, no_edit }) => {
+            let command = resolve(*form, !*no_edit)?;
+
+            SnipsterCommand::run_command(&command)
+        }
+        Some(Commands::Check { file, shellcheck }) => {
+            check(file.as_deref().unwrap_or(SNIPPET_LOCATION), *shellcheck)
+        }
+        Some(Commands::Doctor { json }) => doctor(*json),
+        None => write(false, false, false, None),
+    }
+}
+
+fn write(
+    form: bool,
+    print: bool,
+    edit: bool,
+    tmux_pane: Option<&str>,
+) -> Result<Snipster, SnipsterError> {
+    let command = resolve(form, edit)?;
+
+    if let Some(target) = tmux_pane {
+        send_to_pane(target, &command)
+    } else if print {
+        println!("{}", command);
+        Ok(Snipster { snippet: None })
+    } else {
+        copy_to_clipboard(&command)
+    }
+}
+
+/// Picks a snippet, fills in its placeholders and optionally lets the user edit the
+/// result. Destructive commands must be confirmed before they are returned.
+fn resolve(form: bool, edit: bool) -> Result<String, SnipsterError> {
+    let (snippet, mut command) = pick_and_fill(form)?;
+
+    if edit {
+        command = LineEditor::new(&command)?.run()?;
+    }
+
+    confirm_if_dangerous(&snippet, &command, &load_config()?)?;
 
-    Ok(result?)
+    Ok(command)
+}
+
+fn pick_and_fill(form: bool) -> Result<(Snippet, String), SnipsterError> {
+    loop {
+        let snip = SnipsterCommand::get_snip_with_fzf()?;
+
+        if let Some(snippet) = snip.snippet {
+            let output = if form {
+                SnipsterCommand::edit_command_with_form(&snippet)
+            } else {
+                SnipsterCommand::edit_command_with_input(&snippet)
+            };
+
+            break match output {
+                Ok(output) => {
+                    let command = PlaceHolder::replace_with_value(&snippet, &output)?;
+                    Ok((snippet, command))
+                }
+                // Esc on the first placeholder returns to the snippet list.
+                Err(SnipsterError::Cancelled) => continue,
+                Err(e) => Err(e),
+            };
+        } else {
+            break Err(SnipsterError::CommandError("ERROR".to_string()));
+        }
+    }
 }
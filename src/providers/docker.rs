@@ -0,0 +1,269 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{error::SnipsterError, storage::requirements::is_installed};
+
+use super::{output, Row};
+
+// Previews run whichever engine the rows came from.
+macro_rules! engine {
+    ($command:literal) => {
+        concat!(
+            "$(command -v docker >/dev/null && echo docker || echo podman) ",
+            $command
+        )
+    };
+}
+
+pub const CONTAINER_PREVIEW: &str = engine!("inspect {1}");
+pub const IMAGE_PREVIEW: &str = engine!("image inspect {1}");
+pub const VOLUME_PREVIEW: &str = engine!("volume inspect {1}");
+pub const NETWORK_PREVIEW: &str = engine!("network inspect {1}");
+pub const COMPOSE_PREVIEW: &str = engine!("compose ps --all {1}");
+
+// Looked for in the current directory, then in each parent, like `docker compose`.
+const COMPOSE_FILES: &[&str] = &[
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+/// `docker`, or `podman` when only podman is installed.
+pub fn engine() -> &'static str {
+    if !is_installed("docker") && is_installed("podman") {
+        "podman"
+    } else {
+        "docker"
+    }
+}
+
+/// Containers, stopped ones included unless `--running` is given. The value of a
+/// row is the container name.
+pub fn containers(
+    options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let mut args = vec!["ps"];
+    if !options.contains_key("running") {
+        args.push("--all");
+    }
+
+    let mut rows = vec![Row::header(format!(
+        "{:<30} {:<30} {:<10} STATUS",
+        "NAME", "IMAGE", "STATE"
+    ))];
+
+    for container in list(&args)? {
+        // docker prints `Names` comma separated, podman as an array.
+        let names = match &container["Names"] {
+            Value::Array(names) => names.first().map_or("", text),
+            names => text(names),
+        };
+        let Some(name) = names.split(',').next().filter(|name| !name.is_empty()) else {
+            continue;
+        };
+
+        rows.push(Row::new(
+            name,
+            format!(
+                "{:<30} {:<30} {:<10} {}",
+                name,
+                text(&container["Image"]),
+                text(&container["State"]),
+                text(&container["Status"])
+            ),
+        ));
+    }
+
+    Ok(rows)
+}
+
+/// Images as `repository:tag`, or by ID when untagged.
+pub fn images(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let mut rows = vec![Row::header(format!(
+        "{:<50} {:<12} {:<16} SIZE",
+        "IMAGE", "ID", "CREATED"
+    ))];
+
+    for image in list(&["images"])? {
+        let id = text(&image["ID"]);
+        let id = id.strip_prefix("sha256:").unwrap_or(id);
+        let short_id = &id[..id.len().min(12)];
+        let (repository, tag) = (text(&image["Repository"]), text(&image["Tag"]));
+
+        let reference = if repository.is_empty() || repository == "<none>" || tag == "<none>" {
+            short_id.to_string()
+        } else {
+            format!("{}:{}", repository, tag)
+        };
+
+        rows.push(Row::new(
+            reference.clone(),
+            format!(
+                "{:<50} {:<12} {:<16} {}",
+                reference,
+                short_id,
+                text(&image["CreatedSince"]),
+                text(&image["Size"])
+            ),
+        ));
+    }
+
+    Ok(rows)
+}
+
+/// Volumes with their driver.
+pub fn volumes(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    Ok(list(&["volume", "ls"])?
+        .iter()
+        .map(|volume| {
+            let name = text(&volume["Name"]);
+            Row::new(
+                name,
+                format!(
+                    "\x1b[32m{}\x1b[0m  \x1b[2m{}\x1b[0m",
+                    name,
+                    text(&volume["Driver"])
+                ),
+            )
+        })
+        .collect())
+}
+
+/// Networks with their driver and scope.
+pub fn networks(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    Ok(list(&["network", "ls"])?
+        .iter()
+        .map(|network| {
+            let name = text(&network["Name"]);
+            Row::new(
+                name,
+                format!(
+                    "\x1b[32m{}\x1b[0m  {}  \x1b[2m{}\x1b[0m",
+                    name,
+                    text(&network["Driver"]),
+                    text(&network["Scope"])
+                ),
+            )
+        })
+        .collect())
+}
+
+/// Services of the nearest compose file, with their image or build context.
+pub fn compose_services(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let path = compose_file().ok_or_else(|| {
+        SnipsterError::PlaceHolderError(format!(
+            "no {} in this directory or its parents",
+            COMPOSE_FILES.join(", ")
+        ))
+    })?;
+    let compose = fs::read_to_string(&path)?;
+
+    Ok(services(&compose)
+        .into_iter()
+        .map(|(name, source)| {
+            Row::new(
+                name.clone(),
+                format!(
+                    "\x1b[32m{}\x1b[0m  {}  \x1b[2m{}\x1b[0m",
+                    name,
+                    source,
+                    path.display()
+                ),
+            )
+        })
+        .collect())
+}
+
+/// Runs `<engine> <args> --format '{{json .}}'`, which prints one object per line.
+fn list(args: &[&str]) -> Result<Vec<Value>, SnipsterError> {
+    let mut args = args.to_vec();
+    args.extend(["--format", "{{json .}}"]);
+
+    output(engine(), &args)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+fn compose_file() -> Option<PathBuf> {
+    let cwd = env::current_dir().ok()?;
+
+    cwd.ancestors()
+        .flat_map(|dir| COMPOSE_FILES.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+/// The keys under the top-level `services:` of a compose file, each with its
+/// `image`, or `build` context when it has no image. Reads just enough YAML for
+/// the block style compose files are written in.
+fn services(compose: &str) -> Vec<(String, String)> {
+    let mut services: Vec<(String, String)> = Vec::new();
+    let mut in_services = false;
+    let mut service_indent = None;
+    let mut property_indent = None;
+
+    for line in compose.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let (key, value) = match trimmed.split_once(':') {
+            Some((key, value)) => (key.trim().trim_matches(['"', '\'']), value.trim()),
+            None => continue,
+        };
+
+        if indent == 0 {
+            in_services = key == "services";
+            continue;
+        }
+        if !in_services {
+            continue;
+        }
+
+        let service_indent = *service_indent.get_or_insert(indent);
+        if indent == service_indent {
+            services.push((key.to_string(), String::new()));
+            property_indent = None;
+            continue;
+        }
+        // Only the service's own keys, not those of nested maps like `environment`.
+        if indent != *property_indent.get_or_insert(indent) {
+            continue;
+        }
+        let value = value.trim_matches(['"', '\'']);
+        if let Some((_, source)) = services.last_mut() {
+            match key {
+                "image" => *source = value.to_string(),
+                "build" if source.is_empty() => {
+                    *source = format!("build {}", value).trim_end().to_string()
+                }
+                _ => {}
+            }
+        }
+    }
+
+    services
+}
+
+fn text(value: &Value) -> &str {
+    value.as_str().unwrap_or_default()
+}
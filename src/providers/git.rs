@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use crate::error::SnipsterError;
+
+use super::{output, Row};
+
+pub const LOG_PREVIEW: &str = "git log --oneline --graph --color=always -30 {1}";
+pub const SHOW_PREVIEW: &str = "git show --stat --patch --color=always {1}";
+pub const STASH_PREVIEW: &str = "git stash show --patch --color=always {1}";
+pub const DIFF_PREVIEW: &str =
+    "git diff --color=always -- {1}; git diff --cached --color=always -- {1}";
+
+/// Runs git in the current directory, failing clearly outside a repository.
+fn git(args: &[&str]) -> Result<String, SnipsterError> {
+    if output("git", &["rev-parse", "--is-inside-work-tree"]).is_err() {
+        return Err(SnipsterError::PlaceHolderError(
+            "not inside a git repository".into(),
+        ));
+    }
+
+    output("git", args)
+}
+
+/// Local branches, the checked out one marked with `*`. The value of a row is the
+/// branch name.
+pub fn branches(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    refs("refs/heads")
+}
+
+/// Remote-tracking branches as `origin/main`.
+pub fn remote_branches(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    refs("refs/remotes")
+}
+
+fn refs(namespace: &str) -> Result<Vec<Row>, SnipsterError> {
+    let listing = git(&[
+        "for-each-ref",
+        "--sort=-committerdate",
+        "--format=%(HEAD)%09%(refname:short)%09%(symref)%09%(objectname:short)%09%(committerdate:relative)%09%(subject)",
+        namespace,
+    ])?;
+
+    Ok(listing
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(6, '\t').collect();
+            let [head, name, symref, hash, date, subject] = fields[..] else {
+                return None;
+            };
+            // `origin/HEAD` only points at another remote branch.
+            if !symref.is_empty() {
+                return None;
+            }
+
+            Some(Row::new(
+                name,
+                format!(
+                    "{} \x1b[32m{}\x1b[0m  \x1b[33m{}\x1b[0m  {}  \x1b[2m{}\x1b[0m",
+                    if head == "*" { "*" } else { " " },
+                    name,
+                    hash,
+                    subject,
+                    date
+                ),
+            ))
+        })
+        .collect())
+}
+
+/// The commit graph of the current branch, or of every ref with `--all`. The
+/// value of a row is the abbreviated commit hash.
+pub fn commits(
+    options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let mut args = vec![
+        "log",
+        "--graph",
+        "--color=always",
+        "--max-count=1000",
+        "--format=%x1f%h%x1f%C(auto)%d%C(reset) %s %C(dim)%an, %ar%C(reset)",
+    ];
+    if options.contains_key("all") {
+        args.push("--all");
+    }
+
+    Ok(git(&args)?
+        .lines()
+        .filter_map(|line| {
+            // Lines that only continue the graph carry no commit.
+            let (graph, rest) = line.split_once('\x1f')?;
+            let (hash, description) = rest.split_once('\x1f')?;
+
+            Some(Row::new(
+                hash,
+                format!("{}\x1b[33m{}\x1b[0m{}", graph, hash, description),
+            ))
+        })
+        .collect())
+}
+
+/// Tags, newest first.
+pub fn tags(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let listing = git(&[
+        "for-each-ref",
+        "--sort=-creatordate",
+        "--format=%(refname:short)%09%(creatordate:short)%09%(subject)",
+        "refs/tags",
+    ])?;
+
+    Ok(listing
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let name = fields.next()?;
+            let date = fields.next().unwrap_or_default();
+            let subject = fields.next().unwrap_or_default();
+
+            Some(Row::new(
+                name,
+                format!(
+                    "\x1b[32m{}\x1b[0m  \x1b[2m{}\x1b[0m  {}",
+                    name, date, subject
+                ),
+            ))
+        })
+        .collect())
+}
+
+/// Remotes with their fetch URL.
+pub fn remotes(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    // `origin\tgit@github.com:org/repo.git (fetch)`
+    Ok(git(&["remote", "-v"])?
+        .lines()
+        .filter_map(|line| line.strip_suffix(" (fetch)"))
+        .filter_map(|line| {
+            let (name, url) = line.split_once('\t')?;
+            Some(Row::new(name, format!("\x1b[32m{}\x1b[0m  {}", name, url)))
+        })
+        .collect())
+}
+
+/// Stash entries as `stash@{0}`.
+pub fn stashes(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    Ok(git(&["stash", "list", "--format=%gd%x09%cr%x09%gs"])?
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let name = fields.next()?;
+            let date = fields.next().unwrap_or_default();
+            let message = fields.next().unwrap_or_default();
+
+            Some(Row::new(
+                name,
+                format!(
+                    "\x1b[32m{}\x1b[0m  {}  \x1b[2m{}\x1b[0m",
+                    name, message, date
+                ),
+            ))
+        })
+        .collect())
+}
+
+/// Files with staged or unstaged changes, or untracked, from
+/// `git status --porcelain`. The value of a row is the path relative to the
+/// current directory.
+pub fn changed_files(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let status = git(&["status", "--porcelain=v1", "-z"])?;
+    // Porcelain paths are relative to the top of the work tree.
+    let prefix = output("git", &["rev-parse", "--show-prefix"])?;
+    let prefix = prefix.trim();
+    let cdup = output("git", &["rev-parse", "--show-cdup"])?;
+    let cdup = cdup.trim();
+
+    let mut rows = Vec::new();
+    let mut entries = status.split('\0');
+
+    // `XY path`, followed by the original path as its own entry for renames.
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (code, path) = entry.split_at(3);
+        if code.starts_with(['R', 'C']) {
+            entries.next();
+        }
+
+        let relative = match path.strip_prefix(prefix) {
+            Some(inside) if !prefix.is_empty() => inside.to_string(),
+            _ => format!("{}{}", cdup, path),
+        };
+
+        rows.push(Row::new(
+            relative.clone(),
+            format!("\x1b[33m{}\x1b[0m {}", &code[..2], relative),
+        ));
+    }
+
+    Ok(rows)
+}
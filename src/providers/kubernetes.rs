@@ -0,0 +1,355 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::error::SnipsterError;
+
+use super::{output, Row};
+
+// Previews read earlier choices from the environment `fzf_with_rows` sets.
+macro_rules! scoped {
+    ($command:literal) => {
+        concat!(
+            $command,
+            r#" ${SNIPSTER_K8S_CONTEXT:+--context "$SNIPSTER_K8S_CONTEXT"}"#,
+            r#" ${SNIPSTER_K8S_NAMESPACE:+-n "$SNIPSTER_K8S_NAMESPACE"}"#
+        )
+    };
+}
+
+pub const CONTEXT_PREVIEW: &str = "kubectl config view --minify --context {1}";
+pub const NAMESPACE_PREVIEW: &str =
+    r#"kubectl describe namespace {1} ${SNIPSTER_K8S_CONTEXT:+--context "$SNIPSTER_K8S_CONTEXT"}"#;
+pub const POD_PREVIEW: &str = scoped!("kubectl describe pod {1}");
+pub const DEPLOYMENT_PREVIEW: &str = scoped!("kubectl describe deployment {1}");
+pub const SERVICE_PREVIEW: &str = scoped!("kubectl describe service {1}");
+pub const CONTAINER_PREVIEW: &str = scoped!(r#"kubectl describe pod "$SNIPSTER_POD""#);
+
+/// Contexts of the kubeconfig, the current one marked with `*`.
+pub fn contexts(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let config: Value =
+        serde_json::from_str(&output("kubectl", &["config", "view", "-o", "json"])?)?;
+    let current = text(&config["current-context"]);
+
+    Ok(items(&config["contexts"])
+        .map(|context| {
+            let name = text(&context["name"]);
+            let details = &context["context"];
+
+            Row::new(
+                name,
+                format!(
+                    "{} \x1b[32m{}\x1b[0m  cluster {}  namespace {}",
+                    if name == current { "*" } else { " " },
+                    name,
+                    text(&details["cluster"]),
+                    or_dash(text(&details["namespace"]))
+                ),
+            )
+        })
+        .collect())
+}
+
+/// Namespaces of the `<k8s_context>` picked before, or of the current context.
+pub fn namespaces(
+    _options: &HashMap<String, String>,
+    resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let list = get(&["namespaces"], resolved, false)?;
+
+    Ok(items(&list["items"])
+        .map(|namespace| {
+            let name = text(&namespace["metadata"]["name"]);
+            Row::new(
+                name,
+                format!(
+                    "\x1b[32m{}\x1b[0m  {}",
+                    name,
+                    text(&namespace["status"]["phase"])
+                ),
+            )
+        })
+        .collect())
+}
+
+/// Pods of the `<k8s_namespace>` picked before, or of the context's namespace.
+pub fn pods(
+    _options: &HashMap<String, String>,
+    resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let list = get(&["pods"], resolved, true)?;
+
+    Ok(items(&list["items"])
+        .map(|pod| {
+            let name = text(&pod["metadata"]["name"]);
+            let statuses: Vec<&Value> = items(&pod["status"]["containerStatuses"]).collect();
+            let ready = statuses
+                .iter()
+                .filter(|status| status["ready"].as_bool() == Some(true))
+                .count();
+            let restarts: u64 = statuses
+                .iter()
+                .filter_map(|status| status["restartCount"].as_u64())
+                .sum();
+            // A waiting container's reason, such as `CrashLoopBackOff`, says more
+            // than the pod phase.
+            let state = statuses
+                .iter()
+                .find_map(|status| status["state"]["waiting"]["reason"].as_str())
+                .unwrap_or_else(|| text(&pod["status"]["phase"]));
+
+            Row::new(
+                name,
+                format!(
+                    "\x1b[32m{}\x1b[0m  {}/{}  {}  {} restarts  \x1b[2m{}\x1b[0m",
+                    name,
+                    ready,
+                    statuses.len(),
+                    state,
+                    restarts,
+                    or_dash(text(&pod["spec"]["nodeName"]))
+                ),
+            )
+        })
+        .collect())
+}
+
+/// Deployments of the `<k8s_namespace>` picked before, or of the context's
+/// namespace.
+pub fn deployments(
+    _options: &HashMap<String, String>,
+    resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let list = get(&["deployments"], resolved, true)?;
+
+    Ok(items(&list["items"])
+        .map(|deployment| {
+            let name = text(&deployment["metadata"]["name"]);
+            let status = &deployment["status"];
+
+            Row::new(
+                name,
+                format!(
+                    "\x1b[32m{}\x1b[0m  {}/{} ready  {} up to date",
+                    name,
+                    status["readyReplicas"].as_u64().unwrap_or(0),
+                    deployment["spec"]["replicas"].as_u64().unwrap_or(0),
+                    status["updatedReplicas"].as_u64().unwrap_or(0)
+                ),
+            )
+        })
+        .collect())
+}
+
+/// Services of the `<k8s_namespace>` picked before, or of the context's namespace.
+pub fn services(
+    _options: &HashMap<String, String>,
+    resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let list = get(&["services"], resolved, true)?;
+
+    Ok(items(&list["items"])
+        .map(|service| {
+            let name = text(&service["metadata"]["name"]);
+            let spec = &service["spec"];
+            let ports: Vec<String> = items(&spec["ports"])
+                .map(|port| {
+                    format!(
+                        "{}/{}",
+                        port["port"].as_u64().unwrap_or(0),
+                        text(&port["protocol"])
+                    )
+                })
+                .collect();
+
+            Row::new(
+                name,
+                format!(
+                    "\x1b[32m{}\x1b[0m  {}  {}  {}",
+                    name,
+                    text(&spec["type"]),
+                    or_dash(text(&spec["clusterIP"])),
+                    ports.join(",")
+                ),
+            )
+        })
+        .collect())
+}
+
+/// Containers, init containers included, of the `<pod>` picked before, which
+/// `PlaceHolder::dependencies` asks for when the snippet has none.
+pub fn containers(
+    _options: &HashMap<String, String>,
+    resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let Some(pod) = resolved.get("pod") else {
+        return Err(SnipsterError::PlaceHolderError(
+            "`<k8s_container>` needs a `<pod>` in the same snippet".into(),
+        ));
+    };
+    let pod = get(&["pod", pod], resolved, true)?;
+
+    let spec = &pod["spec"];
+    let init = items(&spec["initContainers"]).map(|container| (container, "init"));
+    let regular = items(&spec["containers"]).map(|container| (container, ""));
+
+    Ok(regular
+        .chain(init)
+        .map(|(container, kind)| {
+            let name = text(&container["name"]);
+            Row::new(
+                name,
+                format!(
+                    "\x1b[32m{}\x1b[0m  {}  \x1b[2m{}\x1b[0m",
+                    name,
+                    text(&container["image"]),
+                    kind
+                ),
+            )
+        })
+        .collect())
+}
+
+/// `kubectl get <args> -o json` in the `<k8s_context>` and, for namespaced
+/// resources, the `<k8s_namespace>` picked before.
+fn get(
+    args: &[&str],
+    resolved: &HashMap<String, String>,
+    namespaced: bool,
+) -> Result<Value, SnipsterError> {
+    let mut command = vec!["get"];
+    command.extend(args);
+    if let Some(context) = resolved.get("k8s_context") {
+        command.extend(["--context", context]);
+    }
+    if let Some(namespace) = resolved.get("k8s_namespace").filter(|_| namespaced) {
+        command.extend(["--namespace", namespace]);
+    }
+    command.extend(["-o", "json"]);
+
+    Ok(serde_json::from_str(&output("kubectl", &command)?)?)
+}
+
+fn items(value: &Value) -> impl Iterator<Item = &Value> {
+    value.as_array().into_iter().flatten()
+}
+
+fn text(value: &Value) -> &str {
+    value.as_str().unwrap_or_default()
+}
+
+fn or_dash(value: &str) -> &str {
+    if value.is_empty() {
+        "-"
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::Once;
+    use std::{env, fs};
+
+    // Answers like kubectl; list items are named after the arguments they were
+    // asked for with, so tests can see how a listing was scoped.
+    const FAKE_KUBECTL: &str = r#"#!/bin/sh
+case "$1 $2" in
+  "config view") echo '{"current-context":"dev","contexts":[
+    {"name":"dev","context":{"cluster":"c1","namespace":"app"}},
+    {"name":"prod","context":{"cluster":"c2"}}]}' ;;
+  "get pods") echo '{"items":[{"metadata":{"name":"web-1"},"spec":{"nodeName":"n1"},
+    "status":{"phase":"Running","containerStatuses":[
+      {"ready":true,"restartCount":2},
+      {"ready":false,"restartCount":1,"state":{"waiting":{"reason":"CrashLoopBackOff"}}}]}}]}' ;;
+  "get pod") echo '{"spec":{"containers":[{"name":"web","image":"nginx:1"}],
+    "initContainers":[{"name":"migrate","image":"app:2"}]}}' ;;
+  get\ *) echo "{\"items\":[{\"metadata\":{\"name\":\"$*\"},\"spec\":{},\"status\":{}}]}" ;;
+  *) echo "unexpected: $*" >&2; exit 1 ;;
+esac
+"#;
+
+    fn fake_kubectl() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            let dir = env::temp_dir().join(format!("snipster-kubectl-{}", std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            let kubectl = dir.join("kubectl");
+            fs::write(&kubectl, FAKE_KUBECTL).unwrap();
+            fs::set_permissions(&kubectl, fs::Permissions::from_mode(0o755)).unwrap();
+
+            let path = env::var_os("PATH").unwrap_or_default();
+            let paths = std::iter::once(dir).chain(env::split_paths(&path));
+            env::set_var("PATH", env::join_paths(paths).unwrap());
+        });
+    }
+
+    fn resolved(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn values(rows: Vec<Row>) -> Vec<String> {
+        rows.into_iter().map(|row| row.value).collect()
+    }
+
+    #[test]
+    fn contexts_mark_the_current_one() {
+        fake_kubectl();
+        let rows = contexts(&HashMap::new(), &HashMap::new()).unwrap();
+
+        assert_eq!(rows[0].value, "dev");
+        assert!(rows[0].display.starts_with("* "));
+        assert!(rows[1].display.contains("namespace -"));
+    }
+
+    #[test]
+    fn pods_show_readiness_and_waiting_reason() {
+        fake_kubectl();
+        let rows = pods(&HashMap::new(), &HashMap::new()).unwrap();
+
+        assert_eq!(rows[0].value, "web-1");
+        assert!(rows[0]
+            .display
+            .contains("1/2  CrashLoopBackOff  3 restarts"));
+    }
+
+    #[test]
+    fn listings_are_scoped_by_earlier_choices() {
+        fake_kubectl();
+        let none = HashMap::new();
+        let scope = resolved(&[("k8s_context", "dev"), ("k8s_namespace", "app")]);
+
+        assert_eq!(
+            values(deployments(&none, &none).unwrap()),
+            vec!["get deployments -o json"]
+        );
+        assert_eq!(
+            values(services(&none, &scope).unwrap()),
+            vec!["get services --context dev --namespace app -o json"]
+        );
+        // Namespaces aren't themselves namespaced.
+        assert_eq!(
+            values(namespaces(&none, &scope).unwrap()),
+            vec!["get namespaces --context dev -o json"]
+        );
+    }
+
+    #[test]
+    fn containers_of_the_chosen_pod() {
+        fake_kubectl();
+        let none = HashMap::new();
+
+        assert!(containers(&none, &none).is_err());
+        assert_eq!(
+            values(containers(&none, &resolved(&[("pod", "web-1")])).unwrap()),
+            vec!["web", "migrate"]
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::error::SnipsterError;
+
+pub mod docker;
+pub mod git;
+pub mod kubernetes;
+pub mod network;
+pub mod package;
+pub mod port;
+pub mod process;
+pub mod ssh;
+pub mod tmux;
+
+/// One pickable item of a built-in provider: the value inserted into the command
+/// and the text shown for it in fzf.
+#[derive(Debug, Clone)]
+pub struct Row {
+    pub value: String,
+    pub display: String,
+}
+
+impl Row {
+    pub fn new(value: impl Into<String>, display: impl Into<String>) -> Self {
+        // Rows are fed to fzf as `value\tdisplay` lines.
+        let clean = |s: String| s.replace(['\t', '\n'], " ");
+
+        Row {
+            value: clean(value.into()),
+            display: clean(display.into()),
+        }
+    }
+
+    /// A line fzf shows but never returns, for use with `FzfBuilder::header`.
+    pub fn header(display: impl Into<String>) -> Self {
+        Row::new("", display)
+    }
+}
+
+/// Lists the rows of a provider implemented in Rust, given the placeholder's
+/// options and the values resolved so far.
+pub type RowSource =
+    fn(&HashMap<String, String>, &HashMap<String, String>) -> Result<Vec<Row>, SnipsterError>;
+
+/// Runs `program` and returns its stdout, failing with its stderr.
+pub(crate) fn output(program: &str, args: &[&str]) -> Result<String, SnipsterError> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| SnipsterError::CommandError(format!("Failed to run `{}`: {}", program, e)))?;
+
+    if !output.status.success() {
+        return Err(SnipsterError::CommandError(format!(
+            "`{} {}` failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
@@ -0,0 +1,154 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+
+use crate::error::SnipsterError;
+
+use super::{output, Row};
+
+/// One interface of `ip -j addr show`.
+#[derive(Deserialize, Debug)]
+struct Link {
+    ifname: String,
+    #[serde(default)]
+    operstate: Option<String>,
+    #[serde(default)]
+    address: Option<String>, // MAC
+    #[serde(default)]
+    addr_info: Vec<AddrInfo>,
+}
+
+#[derive(Deserialize, Debug)]
+struct AddrInfo {
+    #[serde(default)]
+    family: String, // `inet` or `inet6`
+    #[serde(default)]
+    local: Option<String>,
+    #[serde(default)]
+    prefixlen: Option<u8>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+impl AddrInfo {
+    fn cidr(&self) -> Option<String> {
+        let local = self.local.as_ref()?;
+        Some(match self.prefixlen {
+            Some(prefix) => format!("{}/{}", local, prefix),
+            None => local.clone(),
+        })
+    }
+}
+
+/// Lists network interfaces with their state, MAC and addresses. Reads `ip -j`,
+/// or `/sys/class/net` without addresses when `ip` is not available. The value of
+/// a row is the interface name.
+pub fn interfaces(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let links = match ip_addresses() {
+        Ok(links) => links,
+        Err(_) => sys_class_net()?,
+    };
+
+    let mut rows = vec![Row::header(format!(
+        "{:<16} {:<8} {:<17} ADDRESSES",
+        "INTERFACE", "STATE", "MAC"
+    ))];
+
+    for link in links {
+        let cidrs: Vec<String> = link.addr_info.iter().filter_map(AddrInfo::cidr).collect();
+
+        rows.push(Row::new(
+            link.ifname.clone(),
+            format!(
+                "{:<16} {:<8} {:<17} {}",
+                link.ifname,
+                link.operstate.as_deref().unwrap_or("-"),
+                link.address.as_deref().unwrap_or("-"),
+                cidrs.join(" ")
+            ),
+        ));
+    }
+
+    Ok(rows)
+}
+
+/// Lists the addresses of every interface from `ip -j`, `--ipv4` or `--ipv6` for
+/// one family only. The value of a row is the address without its prefix length.
+pub fn addresses(
+    options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let family = match (options.contains_key("ipv4"), options.contains_key("ipv6")) {
+        (true, false) => Some("inet"),
+        (false, true) => Some("inet6"),
+        _ => None,
+    };
+
+    let mut rows = vec![Row::header(format!(
+        "{:<43} {:<6} {:<16} {:<8} SCOPE",
+        "ADDRESS", "FAMILY", "INTERFACE", "STATE"
+    ))];
+
+    for link in ip_addresses()? {
+        for info in &link.addr_info {
+            if family.is_some_and(|family| info.family != family) {
+                continue;
+            }
+            let (Some(local), Some(cidr)) = (&info.local, info.cidr()) else {
+                continue;
+            };
+
+            rows.push(Row::new(
+                local.clone(),
+                format!(
+                    "{:<43} {:<6} {:<16} {:<8} {}",
+                    cidr,
+                    if info.family == "inet6" {
+                        "ipv6"
+                    } else {
+                        "ipv4"
+                    },
+                    link.ifname,
+                    link.operstate.as_deref().unwrap_or("-"),
+                    info.scope.as_deref().unwrap_or("-")
+                ),
+            ));
+        }
+    }
+
+    Ok(rows)
+}
+
+fn ip_addresses() -> Result<Vec<Link>, SnipsterError> {
+    Ok(serde_json::from_str(&output(
+        "ip",
+        &["-j", "addr", "show"],
+    )?)?)
+}
+
+fn sys_class_net() -> Result<Vec<Link>, SnipsterError> {
+    let read = |name: &str, file: &str| {
+        fs::read_to_string(format!("/sys/class/net/{}/{}", name, file))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+
+    let mut links: Vec<Link> = fs::read_dir("/sys/class/net")?
+        .flatten()
+        .map(|entry| {
+            let ifname = entry.file_name().to_string_lossy().to_string();
+            Link {
+                operstate: read(&ifname, "operstate").map(|state| state.to_uppercase()),
+                address: read(&ifname, "address"),
+                addr_info: Vec::new(),
+                ifname,
+            }
+        })
+        .collect();
+    links.sort_by(|a, b| a.ifname.cmp(&b.ifname));
+
+    Ok(links)
+}
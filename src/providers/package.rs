@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use crate::{error::SnipsterError, storage::requirements::is_installed};
+
+use super::{output, Row};
+
+/// Shows the details of the highlighted package with whichever manager knows it.
+pub const INSTALLED_PREVIEW: &str = "dpkg -s {1} 2>/dev/null || rpm -qi {1} 2>/dev/null \
+    || pacman -Qi {1} 2>/dev/null || apk info -a {1} 2>/dev/null \
+    || nix-env -q --description {1} 2>/dev/null || brew info {1} 2>/dev/null";
+pub const AVAILABLE_PREVIEW: &str = "apt-cache show {1} 2>/dev/null \
+    || dnf -C info {1} 2>/dev/null || pacman -Si {1} 2>/dev/null \
+    || apk search -v -d {1} 2>/dev/null || brew info {1} 2>/dev/null";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Manager {
+    Dpkg, // Debian and Ubuntu, installing with apt
+    Rpm,  // Fedora and RHEL, installing with dnf
+    Pacman,
+    Apk,
+    Nix,
+    Brew,
+}
+
+impl Manager {
+    const ALL: [Manager; 6] = [
+        Manager::Dpkg,
+        Manager::Rpm,
+        Manager::Pacman,
+        Manager::Apk,
+        Manager::Nix,
+        Manager::Brew,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Manager::Dpkg => "dpkg",
+            Manager::Rpm => "rpm",
+            Manager::Pacman => "pacman",
+            Manager::Apk => "apk",
+            Manager::Nix => "nix",
+            Manager::Brew => "brew",
+        }
+    }
+
+    // The program that lists installed packages, checked for on `PATH`.
+    fn program(self) -> &'static str {
+        match self {
+            Manager::Dpkg => "dpkg-query",
+            Manager::Nix => "nix-env",
+            other => other.name(),
+        }
+    }
+
+    /// `--manager=<name>` from the placeholder, otherwise the first one installed.
+    fn detect(options: &HashMap<String, String>) -> Result<Self, SnipsterError> {
+        if let Some(name) = options.get("manager") {
+            return Manager::ALL
+                .into_iter()
+                .find(|manager| manager.name() == name)
+                .ok_or_else(|| {
+                    SnipsterError::PlaceHolderError(format!(
+                        "unknown package manager `{}`, expected one of {}",
+                        name,
+                        Manager::ALL.map(Manager::name).join(", ")
+                    ))
+                });
+        }
+
+        Manager::ALL
+            .into_iter()
+            .find(|manager| is_installed(manager.program()))
+            .ok_or_else(|| {
+                SnipsterError::PlaceHolderError(format!(
+                    "no supported package manager found ({})",
+                    Manager::ALL.map(Manager::name).join(", ")
+                ))
+            })
+    }
+
+    /// Installed packages as (name, version).
+    fn installed(self) -> Result<Vec<(String, String)>, SnipsterError> {
+        let packages = match self {
+            Manager::Dpkg => output(
+                "dpkg-query",
+                &["-W", "-f=${db:Status-Abbrev}\t${Package}\t${Version}\n"],
+            )?
+            .lines()
+            .filter(|line| line.starts_with("ii"))
+            .filter_map(|line| {
+                let mut fields = line.split('\t').skip(1);
+                Some((fields.next()?.to_string(), fields.next()?.to_string()))
+            })
+            .collect(),
+            Manager::Rpm => output("rpm", &["-qa", "--qf", "%{NAME}\t%{VERSION}-%{RELEASE}\n"])?
+                .lines()
+                .filter_map(|line| {
+                    let (name, version) = line.split_once('\t')?;
+                    Some((name.to_string(), version.to_string()))
+                })
+                .collect(),
+            Manager::Pacman => output("pacman", &["-Q"])?
+                .lines()
+                .filter_map(|line| {
+                    let (name, version) = line.split_once(' ')?;
+                    Some((name.to_string(), version.to_string()))
+                })
+                .collect(),
+            Manager::Apk => output("apk", &["info", "-v"])?
+                .lines()
+                .filter_map(split_versioned)
+                .collect(),
+            Manager::Nix => output("nix-env", &["-q"])?
+                .lines()
+                .filter_map(split_versioned)
+                .collect(),
+            Manager::Brew => output("brew", &["list", "--versions"])?
+                .lines()
+                .filter_map(|line| {
+                    let (name, versions) = line.split_once(' ')?;
+                    Some((name.to_string(), versions.to_string()))
+                })
+                .collect(),
+        };
+
+        Ok(packages)
+    }
+
+    /// Packages in the local repository metadata, as (name, details), without
+    /// refreshing it.
+    fn available(self) -> Result<Vec<(String, String)>, SnipsterError> {
+        let packages = match self {
+            // Names only; `apt-cache search` over every description takes minutes.
+            Manager::Dpkg => output("apt-cache", &["pkgnames"])?
+                .lines()
+                .map(|name| (name.to_string(), String::new()))
+                .collect(),
+            // `name.arch  version  repo`, after a heading
+            Manager::Rpm => output("dnf", &["-C", "-q", "list", "--available"])?
+                .lines()
+                .filter_map(|line| {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    let [package, version, repo] = fields[..] else {
+                        return None;
+                    };
+                    let name = package.rsplit_once('.').map_or(package, |(name, _)| name);
+                    Some((name.to_string(), format!("{}  {}", version, repo)))
+                })
+                .collect(),
+            // `repo name version [installed]`
+            Manager::Pacman => output("pacman", &["-Sl"])?
+                .lines()
+                .filter_map(|line| {
+                    let mut fields = line.split_whitespace();
+                    let repo = fields.next()?;
+                    let name = fields.next()?;
+                    let version = fields.next()?;
+                    Some((name.to_string(), format!("{}  {}", version, repo)))
+                })
+                .collect(),
+            // `name-version - description`
+            Manager::Apk => output("apk", &["search", "-v", "-d", "."])?
+                .lines()
+                .filter_map(|line| {
+                    let (package, description) = line.split_once(" - ").unwrap_or((line, ""));
+                    let (name, version) = split_versioned(package)?;
+                    Some((name, format!("{}  {}", version, description)))
+                })
+                .collect(),
+            Manager::Nix => output("nix-env", &["-qa"])?
+                .lines()
+                .filter_map(split_versioned)
+                .collect(),
+            Manager::Brew => output("brew", &["formulae"])?
+                .lines()
+                .map(|name| (name.to_string(), String::new()))
+                .collect(),
+        };
+
+        Ok(packages)
+    }
+}
+
+/// Installed packages and their versions from the detected package manager.
+/// `--manager=<name>` picks one of dpkg, rpm, pacman, apk, nix or brew. The value
+/// of a row is the package name.
+pub fn installed(
+    options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let manager = Manager::detect(options)?;
+
+    Ok(rows(manager, manager.installed()?))
+}
+
+/// Packages the detected package manager can install, from its local metadata
+/// cache. Takes the same `--manager` option as `installed`.
+pub fn available(
+    options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let manager = Manager::detect(options)?;
+
+    Ok(rows(manager, manager.available()?))
+}
+
+fn rows(manager: Manager, mut packages: Vec<(String, String)>) -> Vec<Row> {
+    packages.sort();
+    packages.dedup_by(|a, b| a.0 == b.0);
+
+    let width = packages
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .min(40);
+
+    packages
+        .into_iter()
+        .map(|(name, details)| {
+            let display = format!(
+                "{:<width$}  {}  \x1b[2m{}\x1b[0m",
+                name,
+                details,
+                manager.name(),
+                width = width
+            );
+            Row::new(name, display)
+        })
+        .collect()
+}
+
+/// Splits `musl-utils-1.2.4-r2` at the first `-` that starts a version.
+fn split_versioned(package: &str) -> Option<(String, String)> {
+    let index = package
+        .char_indices()
+        .find(|&(i, c)| {
+            c == '-'
+                && package[i + 1..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_digit())
+        })
+        .map(|(i, _)| i)?;
+
+    Some((
+        package[..index].to_string(),
+        package[index + 1..].to_string(),
+    ))
+}
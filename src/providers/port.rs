@@ -0,0 +1,192 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr, TcpListener};
+
+use crate::error::SnipsterError;
+
+use super::{process, Row};
+
+// Ports development servers commonly default to, offered by `<free_port>`.
+const COMMON_PORTS: &[u16] = &[
+    3000, 3001, 4000, 5000, 5173, 8000, 8008, 8080, 8081, 8443, 8888, 9000, 9090,
+];
+
+const TCP_LISTEN: &str = "0A";
+const UDP_UNCONNECTED: &str = "07";
+
+struct Socket {
+    port: u16,
+    proto: &'static str,
+    address: String,
+    inode: u64,
+}
+
+/// Lists listening TCP and bound UDP sockets with the process owning each, from
+/// `/proc/net`. `--tcp` or `--udp` limit the protocol. The value of a row is the
+/// port.
+pub fn ports(
+    options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let mut sockets = listening_sockets()?;
+    if options.contains_key("tcp") != options.contains_key("udp") {
+        let wanted = if options.contains_key("tcp") {
+            "tcp"
+        } else {
+            "udp"
+        };
+        sockets.retain(|socket| socket.proto.starts_with(wanted));
+    }
+    sockets.sort_by(|a, b| (a.port, a.proto).cmp(&(b.port, b.proto)));
+
+    let owners = socket_owners();
+    let mut rows = vec![Row::header(format!(
+        "{:>5}  {:<5} {:<39} {:>7}  COMMAND",
+        "PORT", "PROTO", "ADDRESS", "PID"
+    ))];
+
+    for socket in sockets {
+        // Sockets of other users' processes are only visible to root.
+        let (pid, command) = match owners.get(&socket.inode) {
+            Some(&pid) => (
+                pid.to_string(),
+                process::name(pid).unwrap_or_else(|| "?".to_string()),
+            ),
+            None => ("-".to_string(), "-".to_string()),
+        };
+
+        rows.push(Row::new(
+            socket.port.to_string(),
+            format!(
+                "{:>5}  {:<5} {:<39} {:>7}  {}",
+                socket.port, socket.proto, socket.address, pid, command
+            ),
+        ));
+    }
+
+    Ok(rows)
+}
+
+/// Local ports nothing listens on: one assigned by the system, then the common
+/// development ports that are free.
+pub fn free_ports(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let used: HashSet<u16> = listening_sockets()?
+        .into_iter()
+        .map(|socket| socket.port)
+        .collect();
+
+    let mut rows = Vec::new();
+
+    let assigned = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?
+        .local_addr()?
+        .port();
+    rows.push(Row::new(
+        assigned.to_string(),
+        format!("{:>5}  assigned by the system", assigned),
+    ));
+
+    for port in COMMON_PORTS.iter().filter(|port| !used.contains(port)) {
+        rows.push(Row::new(port.to_string(), format!("{:>5}", port)));
+    }
+
+    Ok(rows)
+}
+
+fn listening_sockets() -> Result<Vec<Socket>, SnipsterError> {
+    let mut sockets = Vec::new();
+
+    for (proto, state) in [
+        ("tcp", TCP_LISTEN),
+        ("tcp6", TCP_LISTEN),
+        ("udp", UDP_UNCONNECTED),
+        ("udp6", UDP_UNCONNECTED),
+    ] {
+        // A kernel without IPv6 has no `tcp6`/`udp6`.
+        let Ok(table) = fs::read_to_string(format!("/proc/net/{}", proto)) else {
+            continue;
+        };
+
+        for line in table.lines().skip(1) {
+            // `sl local_address rem_address st tx_queue rx_queue tr tm->when retrnsmt uid timeout inode`
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != state {
+                continue;
+            }
+            let Some((address, port)) = parse_address(fields[1]) else {
+                continue;
+            };
+
+            sockets.push(Socket {
+                port,
+                proto,
+                address,
+                inode: fields[9].parse().unwrap_or_default(),
+            });
+        }
+    }
+
+    Ok(sockets)
+}
+
+/// Decodes `0100007F:0035`. The address is printed as 32-bit words in host byte
+/// order, the port in plain hex.
+fn parse_address(field: &str) -> Option<(String, u16)> {
+    let (address, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+
+    let mut bytes = Vec::with_capacity(16);
+    for word in address.as_bytes().chunks(8) {
+        let word = u32::from_str_radix(std::str::from_utf8(word).ok()?, 16).ok()?;
+        bytes.extend(word.to_ne_bytes());
+    }
+
+    let address = match bytes.len() {
+        4 => Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]).to_string(),
+        16 => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Ipv6Addr::from(octets).to_string()
+        }
+        _ => return None,
+    };
+
+    Some((address, port))
+}
+
+/// Socket inodes mapped to the process holding them open.
+fn socket_owners() -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return owners;
+    };
+
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(format!("/proc/{}/fd", pid)) else {
+            continue;
+        };
+
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            // `socket:[12345]`
+            let inode = target
+                .to_str()
+                .and_then(|target| target.strip_prefix("socket:["))
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(|inode| inode.parse().ok());
+
+            if let Some(inode) = inode {
+                owners.entry(inode).or_insert(pid);
+            }
+        }
+    }
+
+    owners
+}
@@ -0,0 +1,320 @@
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+
+use crate::error::SnipsterError;
+
+use super::Row;
+
+/// Shows the full command line and the kernel's status of the highlighted process.
+pub const PREVIEW: &str = r"tr '\0' ' ' < /proc/{1}/cmdline; echo; echo; cat /proc/{1}/status";
+
+const USER_WIDTH: usize = 12;
+
+struct Process {
+    pid: u32,
+    ppid: u32,
+    uid: u32,
+    name: String, // `comm`, the executable name
+    cmdline: String,
+    cpu: f64, // percent of one CPU over the process lifetime, like `ps`
+    mem: f64, // resident memory as percent of the total
+    start: i64,
+}
+
+/// Lists processes from `/proc`, with `--mine` for the current user's only,
+/// `--name=<regex>` to match the name or command line and `--tree` to indent
+/// children under their parent. The value of a row is its PID.
+pub fn processes(
+    options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let pattern = match options.get("name") {
+        Some(pattern) => Some(Regex::new(&format!("(?i){}", pattern)).map_err(|e| {
+            SnipsterError::PlaceHolderError(format!("invalid `--name` pattern: {}", e))
+        })?),
+        None => None,
+    };
+    // SAFETY: getuid cannot fail.
+    let uid = unsafe { libc::getuid() };
+
+    let mut processes: Vec<Process> = read_processes()?
+        .into_iter()
+        .filter(|p| !options.contains_key("mine") || p.uid == uid)
+        .filter(|p| {
+            pattern
+                .as_ref()
+                .is_none_or(|re| re.is_match(&p.name) || re.is_match(&p.cmdline))
+        })
+        .collect();
+    processes.sort_by_key(|p| p.pid);
+
+    let users = user_names();
+    let now = now();
+    let mut rows = vec![Row::header(format!(
+        "{:>7} {:>7} {:<width$} {:>5} {:>5} {:>5} COMMAND",
+        "PID",
+        "PPID",
+        "USER",
+        "%CPU",
+        "%MEM",
+        "START",
+        width = USER_WIDTH
+    ))];
+
+    let depths = if options.contains_key("tree") {
+        tree_order(&mut processes)
+    } else {
+        vec![0; processes.len()]
+    };
+
+    for (process, depth) in processes.iter().zip(depths) {
+        let user = users
+            .get(&process.uid)
+            .cloned()
+            .unwrap_or_else(|| process.uid.to_string());
+        let branch = match depth {
+            0 => String::new(),
+            depth => format!("{}└─ ", "   ".repeat(depth - 1)),
+        };
+
+        rows.push(Row::new(
+            process.pid.to_string(),
+            format!(
+                "{:>7} {:>7} {:<width$} {:>5.1} {:>5.1} {:>5} {}{}",
+                process.pid,
+                process.ppid,
+                truncate(&user, USER_WIDTH),
+                process.cpu,
+                process.mem,
+                format_start(process.start, now),
+                branch,
+                process.cmdline,
+                width = USER_WIDTH
+            ),
+        ));
+    }
+
+    Ok(rows)
+}
+
+/// The executable name of `pid`, from `/proc/<pid>/comm`.
+pub fn name(pid: u32) -> Option<String> {
+    fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|name| name.trim().to_string())
+}
+
+fn read_processes() -> Result<Vec<Process>, SnipsterError> {
+    // SAFETY: sysconf only reads configuration values.
+    let ticks = match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        n if n > 0 => n as f64,
+        _ => 100.0,
+    };
+    let page_size = match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        n if n > 0 => n as f64,
+        _ => 4096.0,
+    };
+
+    let uptime: f64 = fs::read_to_string("/proc/uptime")?
+        .split_whitespace()
+        .next()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_default();
+    let boot_time: i64 = field_after(&fs::read_to_string("/proc/stat")?, "btime")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_default();
+    let mem_total: f64 = field_after(&fs::read_to_string("/proc/meminfo")?, "MemTotal:")
+        .and_then(|s| s.parse::<f64>().ok())
+        .map(|kb| kb * 1024.0)
+        .unwrap_or(f64::MAX);
+
+    let mut processes = Vec::new();
+
+    for entry in fs::read_dir("/proc")? {
+        let Some(pid) = entry?.file_name().to_str().and_then(|s| s.parse().ok()) else {
+            continue;
+        };
+
+        // Processes can exit while we read them; skip those.
+        let Ok(stat) = fs::read_to_string(format!("/proc/{}/stat", pid)) else {
+            continue;
+        };
+        let Some((name, fields)) = parse_stat(&stat) else {
+            continue;
+        };
+        let number = |index: usize| -> f64 {
+            fields
+                .get(index)
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_default()
+        };
+
+        let status = fs::read_to_string(format!("/proc/{}/status", pid)).unwrap_or_default();
+        let uid = field_after(&status, "Uid:")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default();
+
+        let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).unwrap_or_default();
+        let cmdline = String::from_utf8_lossy(&cmdline)
+            .split('\0')
+            .filter(|arg| !arg.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // Fields counted from the state, the first one after the name.
+        let started = number(19) / ticks;
+        let elapsed = (uptime - started).max(1.0 / ticks);
+
+        processes.push(Process {
+            pid,
+            ppid: number(1) as u32,
+            uid,
+            cmdline: if cmdline.is_empty() {
+                format!("[{}]", name)
+            } else {
+                cmdline
+            },
+            name,
+            cpu: (number(11) + number(12)) / ticks / elapsed * 100.0,
+            mem: number(21) * page_size / mem_total * 100.0,
+            start: boot_time + started as i64,
+        });
+    }
+
+    Ok(processes)
+}
+
+/// Splits `/proc/<pid>/stat` into the name and the fields after it. The name is
+/// in parentheses and may itself contain spaces and parentheses.
+fn parse_stat(stat: &str) -> Option<(String, Vec<&str>)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+
+    Some((
+        stat[open + 1..close].to_string(),
+        stat[close + 1..].split_whitespace().collect(),
+    ))
+}
+
+/// First word after the line starting with `key`.
+fn field_after<'a>(contents: &'a str, key: &str) -> Option<&'a str> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix(key))
+        .and_then(|rest| rest.split_whitespace().next())
+}
+
+fn user_names() -> HashMap<u32, String> {
+    fs::read_to_string("/etc/passwd")
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
+
+/// Reorders `processes` depth first from the ones whose parent is not listed,
+/// returning the depth of each.
+fn tree_order(processes: &mut Vec<Process>) -> Vec<usize> {
+    fn visit(
+        index: usize,
+        depth: usize,
+        children: &HashMap<u32, Vec<usize>>,
+        processes: &[Process],
+        order: &mut Vec<(usize, usize)>,
+    ) {
+        order.push((index, depth));
+        for &child in children.get(&processes[index].pid).into_iter().flatten() {
+            visit(child, depth + 1, children, processes, order);
+        }
+    }
+
+    let pids: HashMap<u32, usize> = processes
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.pid, i))
+        .collect();
+    let mut children: HashMap<u32, Vec<usize>> = HashMap::new();
+    let mut roots = Vec::new();
+    for (i, process) in processes.iter().enumerate() {
+        // The kernel's own root has pid 0 as parent and itself in some namespaces.
+        if process.ppid != process.pid && pids.contains_key(&process.ppid) {
+            children.entry(process.ppid).or_default().push(i);
+        } else {
+            roots.push(i);
+        }
+    }
+
+    let mut order = Vec::with_capacity(processes.len());
+    for root in roots {
+        visit(root, 0, &children, processes, &mut order);
+    }
+
+    let mut taken: Vec<Option<Process>> = processes.drain(..).map(Some).collect();
+    let mut depths = Vec::with_capacity(order.len());
+    for (index, depth) in order {
+        if let Some(process) = taken[index].take() {
+            processes.push(process);
+            depths.push(depth);
+        }
+    }
+
+    depths
+}
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// `HH:MM` for processes started today, `MonDD` before, like the `START` of `ps`.
+fn format_start(start: i64, now: i64) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let (Some(started), Some(today)) = (local_time(start), local_time(now)) else {
+        return "?".to_string();
+    };
+
+    if started.tm_year == today.tm_year && started.tm_yday == today.tm_yday {
+        format!("{:02}:{:02}", started.tm_hour, started.tm_min)
+    } else {
+        format!(
+            "{}{:02}",
+            MONTHS[started.tm_mon as usize % 12],
+            started.tm_mday
+        )
+    }
+}
+
+fn local_time(time: i64) -> Option<libc::tm> {
+    let time = time as libc::time_t;
+    // SAFETY: `tm` is plain data, and localtime_r writes it only through the
+    // pointer we pass.
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            None
+        } else {
+            Some(tm)
+        }
+    }
+}
+
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() > width {
+        let mut short: String = s.chars().take(width - 1).collect();
+        short.push('+');
+        short
+    } else {
+        s.to_string()
+    }
+}
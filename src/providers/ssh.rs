@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::SnipsterError;
+
+use super::Row;
+
+/// Shows what ssh will connect to for the highlighted host.
+pub const PREVIEW: &str =
+    "ssh -G {1} 2>/dev/null | grep -iE '^(hostname|user|port|identityfile|proxyjump) '";
+
+// ssh gives up on `Include` nested deeper than this.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+#[derive(Debug, Default)]
+struct Host {
+    alias: String,
+    hostname: Option<String>,
+    user: Option<String>,
+    port: Option<String>,
+    known_host: bool, // only seen in known_hosts
+}
+
+/// Hosts named in `~/.ssh/config` and the files it includes, then those of
+/// `~/.ssh/known_hosts` not already listed. Wildcard patterns and hashed entries
+/// are left out. The value of a row is the alias as typed after `ssh`.
+pub fn hosts(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let Some(ssh_dir) = env::var_os("HOME").map(|home| PathBuf::from(home).join(".ssh")) else {
+        return Err(SnipsterError::PlaceHolderError("HOME is not set".into()));
+    };
+
+    let mut hosts = Vec::new();
+    read_config(&ssh_dir, &ssh_dir.join("config"), &mut hosts, 0);
+    read_known_hosts(&ssh_dir.join("known_hosts"), &mut hosts);
+
+    let mut rows = vec![Row::header(format!(
+        "{:<24} {:<32} {:<12} PORT",
+        "ALIAS", "HOSTNAME", "USER"
+    ))];
+
+    for host in hosts {
+        rows.push(Row::new(
+            host.alias.clone(),
+            format!(
+                "{:<24} {:<32} {:<12} {}{}",
+                host.alias,
+                host.hostname.as_deref().unwrap_or("-"),
+                host.user.as_deref().unwrap_or("-"),
+                host.port.as_deref().unwrap_or("-"),
+                if host.known_host {
+                    "  \x1b[2mknown_hosts\x1b[0m"
+                } else {
+                    ""
+                }
+            ),
+        ));
+    }
+
+    Ok(rows)
+}
+
+/// Adds the hosts of one config file. As in ssh, the first value given for a
+/// host wins and relative `Include` paths are taken from `~/.ssh`.
+fn read_config(ssh_dir: &Path, path: &Path, hosts: &mut Vec<Host>, depth: usize) {
+    if depth > MAX_INCLUDE_DEPTH {
+        return;
+    }
+    let Ok(config) = fs::read_to_string(path) else {
+        return;
+    };
+
+    // Indices of the hosts the current `Host` block applies to.
+    let mut block: Vec<usize> = Vec::new();
+
+    for line in config.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // `Keyword value` or `Keyword=value`
+        let (keyword, value) = line
+            .split_once(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or((line, ""));
+        let keyword = keyword.to_lowercase();
+        let value = value.trim_start_matches(|c: char| c.is_whitespace() || c == '=');
+        let unquoted = value.trim_matches('"');
+
+        match keyword.as_str() {
+            "include" => {
+                for pattern in value.split_whitespace() {
+                    for included in expand_include(ssh_dir, pattern) {
+                        read_config(ssh_dir, &included, hosts, depth + 1);
+                    }
+                }
+            }
+            "host" => {
+                block.clear();
+                for alias in value.split_whitespace() {
+                    if alias.starts_with('!') || is_pattern(alias) {
+                        continue;
+                    }
+                    let index = match hosts.iter().position(|host| host.alias == alias) {
+                        Some(index) => index,
+                        None => {
+                            hosts.push(Host {
+                                alias: alias.to_string(),
+                                ..Host::default()
+                            });
+                            hosts.len() - 1
+                        }
+                    };
+                    block.push(index);
+                }
+            }
+            // Conditional blocks don't name hosts.
+            "match" => block.clear(),
+            "hostname" | "user" | "port" => {
+                for &index in &block {
+                    let host = &mut hosts[index];
+                    let field = match keyword.as_str() {
+                        "hostname" => &mut host.hostname,
+                        "user" => &mut host.user,
+                        _ => &mut host.port,
+                    };
+                    field.get_or_insert_with(|| unquoted.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Resolves an `Include` argument to files, expanding `~` and wildcards in the
+/// file name.
+fn expand_include(ssh_dir: &Path, pattern: &str) -> Vec<PathBuf> {
+    let path = match pattern.strip_prefix("~/") {
+        Some(rest) => match env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(rest),
+            None => return Vec::new(),
+        },
+        None => ssh_dir.join(pattern),
+    };
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if !is_pattern(&name) {
+        return vec![path];
+    }
+
+    let Some(Ok(entries)) = path.parent().map(fs::read_dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| wildcard_match(&name, &entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    // ssh reads glob matches in sorted order.
+    files.sort();
+
+    files
+}
+
+/// Adds the hosts of known_hosts that the config doesn't already name, skipping
+/// hashed (`|1|...`) and `@cert-authority`/`@revoked` lines.
+fn read_known_hosts(path: &Path, hosts: &mut Vec<Host>) {
+    let Ok(known_hosts) = fs::read_to_string(path) else {
+        return;
+    };
+
+    for line in known_hosts.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', '|', '@']) {
+            continue;
+        }
+        let Some(names) = line.split_whitespace().next() else {
+            continue;
+        };
+
+        // `host,10.0.0.1` or `[host]:2222`
+        for name in names.split(',') {
+            let (name, port) = match name
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once("]:"))
+            {
+                Some((name, port)) => (name, Some(port.to_string())),
+                None => (name, None),
+            };
+            if name.starts_with('!') || is_pattern(name) {
+                continue;
+            }
+
+            let known = hosts
+                .iter()
+                .any(|host| host.alias == name || host.hostname.as_deref() == Some(name));
+            if !known {
+                hosts.push(Host {
+                    alias: name.to_string(),
+                    port,
+                    known_host: true,
+                    ..Host::default()
+                });
+            }
+        }
+    }
+}
+
+fn is_pattern(name: &str) -> bool {
+    name.contains(['*', '?'])
+}
+
+/// Matches `name` against a pattern of `*` (any run of characters) and `?` (any one
+/// character).
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Where the last `*` was and the name position it has matched up to.
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
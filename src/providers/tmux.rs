@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use crate::error::SnipsterError;
+
+use super::{output, Row};
+
+pub const SESSION_PREVIEW: &str = "tmux list-windows -t {1}";
+pub const WINDOW_PREVIEW: &str = "tmux list-panes -t {1}";
+pub const PANE_PREVIEW: &str = "tmux capture-pane -ep -t {1}";
+
+/// Sessions of the running tmux server. The value of a row is the session name.
+pub fn sessions(
+    _options: &HashMap<String, String>,
+    _resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    list(
+        &["list-sessions"],
+        "#{session_name}\t#{session_windows} windows\t#{?session_attached,attached,}",
+    )
+}
+
+/// Windows as `session:index`, of the `<tmux_session>` picked before when there
+/// is one.
+pub fn windows(
+    _options: &HashMap<String, String>,
+    resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let mut args = vec!["list-windows"];
+    match resolved.get("tmux_session") {
+        Some(session) => args.extend(["-t", session]),
+        None => args.push("-a"),
+    }
+
+    list(
+        &args,
+        "#{session_name}:#{window_index}\t#{window_name}\t#{window_panes} panes\t#{?window_active,active,}",
+    )
+}
+
+/// Panes as `session:window.pane`, within the `<tmux_window>` or `<tmux_session>`
+/// picked before when there is one.
+pub fn panes(
+    _options: &HashMap<String, String>,
+    resolved: &HashMap<String, String>,
+) -> Result<Vec<Row>, SnipsterError> {
+    let mut args = vec!["list-panes"];
+    match (resolved.get("tmux_window"), resolved.get("tmux_session")) {
+        (Some(window), _) => args.extend(["-t", window]),
+        (None, Some(session)) => args.extend(["-s", "-t", session]),
+        (None, None) => args.push("-a"),
+    }
+
+    list(
+        &args,
+        "#{session_name}:#{window_index}.#{pane_index}\t#{pane_current_command}\t#{pane_current_path}\t#{?pane_active,active,}",
+    )
+}
+
+/// Runs a `tmux list-*` command whose format starts with the target, followed by
+/// tab separated details.
+fn list(args: &[&str], format: &str) -> Result<Vec<Row>, SnipsterError> {
+    let mut args = args.to_vec();
+    args.extend(["-F", format]);
+
+    let listing = output("tmux", &args).map_err(|e| match e {
+        SnipsterError::CommandError(message) if message.contains("no server running") => {
+            SnipsterError::PlaceHolderError("no tmux server is running".into())
+        }
+        e => e,
+    })?;
+
+    Ok(listing
+        .lines()
+        .filter_map(|line| {
+            let (target, details) = line.split_once('\t')?;
+            let details: Vec<&str> = details.split('\t').filter(|d| !d.is_empty()).collect();
+
+            Some(Row::new(
+                target,
+                format!("\x1b[32m{}\x1b[0m  {}", target, details.join("  ")),
+            ))
+        })
+        .collect())
+}
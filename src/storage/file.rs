@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
 use std::io::Read;
@@ -7,15 +7,49 @@ use crate::error::SnipsterError;
 
 use super::placeholder::PlaceHolder;
 
-const SNIPPET_LOCATION: &str = "./snippets.json";
+pub const SNIPPET_LOCATION: &str = "./snippets.json";
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Snippet {
     pub name: String,
     pub content: String,
     pub note: String,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "lenient_placeholders")]
     pub placeholders: Vec<PlaceHolder>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sources: HashMap<String, String>, // Provider commands keyed by placeholder name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub danger: Option<Danger>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>, // Programs the command needs besides those of its providers
+}
+
+/// How destructive a snippet's command is, as declared by its author. `high` always
+/// asks for confirmation; otherwise only commands matching a danger rule do.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Danger {
+    Low,
+    High,
+}
+
+// Hand-written files name placeholders that have no variant; keep them as `Unknown`
+// instead of rejecting the whole file.
+fn lenient_placeholders<'de, D>(deserializer: D) -> Result<Vec<PlaceHolder>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let values = Vec::<serde_json::Value>::deserialize(deserializer)?;
+
+    Ok(values
+        .into_iter()
+        .map(|value| {
+            PlaceHolder::deserialize(&value).unwrap_or_else(|_| match value {
+                serde_json::Value::String(name) => PlaceHolder::Unknown(name),
+                other => PlaceHolder::Unknown(other.to_string()),
+            })
+        })
+        .collect())
 }
 
 pub struct Snipster {
@@ -0,0 +1,86 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::SnipsterError;
+
+/// A transform applied to a resolved value before it is substituted,
+/// written as `<file|basename>` or `<port|default:8080>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    Basename,        // /tmp/archive.tar.gz -> archive.tar.gz
+    Dirname,         // /tmp/archive.tar.gz -> /tmp
+    Stem,            // /tmp/archive.tar.gz -> archive.tar
+    Upper,           // alice -> ALICE
+    Lower,           // ALICE -> alice
+    Default(String), // used when the value is empty
+    Fmt(String),     // reformats a date with `date -d <value> +<fmt>`
+}
+
+impl Filter {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (s, None),
+        };
+
+        match (name, arg) {
+            ("basename", None) => Ok(Filter::Basename),
+            ("dirname", None) => Ok(Filter::Dirname),
+            ("stem", None) => Ok(Filter::Stem),
+            ("upper", None) => Ok(Filter::Upper),
+            ("lower", None) => Ok(Filter::Lower),
+            ("default", Some(arg)) => Ok(Filter::Default(arg.to_string())),
+            ("fmt", Some(arg)) => Ok(Filter::Fmt(arg.to_string())),
+            ("default" | "fmt", None) => Err(format!("filter `{}` needs an argument", name)),
+            (_, Some(_)) if is_plain(name) => Err(format!("filter `{}` takes no argument", name)),
+            _ => Err(format!("unknown filter `{}`", name)),
+        }
+    }
+
+    pub fn apply(&self, value: &str) -> Result<String, SnipsterError> {
+        let path = Path::new(value);
+
+        let result = match self {
+            Filter::Basename => file_part(path.file_name()),
+            Filter::Dirname => match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => {
+                    parent.to_string_lossy().to_string()
+                }
+                _ => ".".to_string(),
+            },
+            Filter::Stem => file_part(path.file_stem()),
+            Filter::Upper => value.to_uppercase(),
+            Filter::Lower => value.to_lowercase(),
+            Filter::Default(default) if value.is_empty() => default.clone(),
+            Filter::Default(_) => value.to_string(),
+            Filter::Fmt(format) => {
+                let output = Command::new("date")
+                    .arg("-d")
+                    .arg(value)
+                    .arg(format!("+{}", format))
+                    .output()?;
+
+                if !output.status.success() {
+                    return Err(SnipsterError::CommandError(format!(
+                        "Cannot format `{}` as a date: {}",
+                        value,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )));
+                }
+
+                String::from_utf8(output.stdout)?.trim().to_string()
+            }
+        };
+
+        Ok(result)
+    }
+}
+
+fn is_plain(name: &str) -> bool {
+    matches!(name, "basename" | "dirname" | "stem" | "upper" | "lower")
+}
+
+fn file_part(part: Option<&std::ffi::OsStr>) -> String {
+    part.map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
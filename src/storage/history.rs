@@ -0,0 +1,42 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+use crate::error::SnipsterError;
+
+const HISTORY_LOCATION: &str = "./.snipster_history";
+const HISTORY_LIMIT: usize = 500;
+
+/// Commands accepted in the final edit step, oldest first.
+pub fn load_history() -> Result<Vec<String>, SnipsterError> {
+    if !std::path::Path::new(HISTORY_LOCATION).exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(HISTORY_LOCATION)?;
+
+    Ok(contents.lines().map(str::to_string).collect())
+}
+
+pub fn append_history(command: &str) -> Result<(), SnipsterError> {
+    // One entry per line, so multi-line commands are not kept.
+    if command.contains('\n') || command.trim().is_empty() {
+        return Ok(());
+    }
+
+    let mut history = load_history()?;
+    history.retain(|entry| entry != command);
+    history.push(command.to_string());
+    let start = history.len().saturating_sub(HISTORY_LIMIT);
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(HISTORY_LOCATION)?;
+
+    for entry in &history[start..] {
+        writeln!(file, "{}", entry)?;
+    }
+
+    Ok(())
+}
@@ -1,2 +1,7 @@
 pub mod file;
+pub mod filter;
+pub mod history;
 pub mod placeholder;
+pub mod requirements;
+pub mod shell;
+pub mod template;
@@ -1,277 +1,806 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use crate::{
     commands::{commands::SnipsterCommand, fzf_builder::FzfBuilder},
     error::SnipsterError,
+    providers::{docker, git, kubernetes, network, package, port, process, ssh, tmux, RowSource},
 };
 
-use super::file::Snippet;
+use super::{
+    file::Snippet,
+    shell::{QuoteContext, QuoteScanner, Shell},
+    template::{Choice, Segment, Template},
+};
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum PlaceHolder {
-    PID,       // Process ID (for commands like `kill`)
-    File,      // File name/path (for commands like `cp`, `mv`, `rm`)
-    Directory, // Directory path (for commands like `cd`, `ls`)
-    Container, // Docker container ID or name (for `docker stop`, `docker rm`)
-    Image,     // Docker image ID or name (for `docker rmi`, `docker pull`)
-    Port,      // Network port (for commands like `netstat`, `kill`)
-    User,      // Username (for `sudo`, `killall`, `chown`)
-    Group,     // Group name (for `chgrp`, `groups`)
-    Command,   // Command name (for `man`, `which`, `killall`)
-    Package,   // Package name (for `apt-get`, `yum`, `brew`)
-    Interface, // Network interface (for `ifconfig`, `ip`)
-    Service,   // Service name (for `systemctl`, `service`)
-    IPAddress, // IP address (for `ping`, `traceroute`, `ssh`)
-    URL,       // URL (for `curl`, `wget`)
-    Device,    // Device name (for `mount`, `umount`, `lsblk`)
-    Disk,      // Disk name or partition (for `df`, `fsck`)
-    Shell,     // Shell type (for `chsh`)
-    Date,      // Date string (for `date`, `touch`)
-    Time,      // Time string (for scheduling or logging)
-    Signal,    // Signal type (e.g., `SIGKILL`, `SIGTERM`, for `kill -s`)
+    PID,              // Process ID (for commands like `kill`)
+    File,             // File name/path (for commands like `cp`, `mv`, `rm`)
+    Directory,        // Directory path (for commands like `cd`, `ls`)
+    Container,        // Docker container name, stopped too (for `docker start`, `docker rm`)
+    Image,            // Docker image as `repo:tag` (for `docker rmi`, `docker run`)
+    Volume,           // Docker volume (for `docker volume rm`, `docker run -v`)
+    Network,          // Docker network (for `docker network connect`)
+    ComposeService,   // Service of the nearest compose file (for `docker compose logs`)
+    Port,             // Network port (for commands like `netstat`, `kill`)
+    FreePort,         // Unused local port (for `python -m http.server`, `ssh -L`)
+    User,             // Username (for `sudo`, `killall`, `chown`)
+    Group,            // Group name (for `chgrp`, `groups`)
+    Command,          // Command name (for `man`, `which`, `killall`)
+    Package,          // Package name (for `apt-get`, `yum`, `brew`)
+    AvailablePackage, // Installable package name (for `apt install`, `dnf install`)
+    Interface,        // Network interface (for `ifconfig`, `ip`)
+    Service,          // Service name (for `systemctl`, `service`)
+    IPAddress,        // IP address (for `ping`, `traceroute`, `ssh`)
+    SshHost,          // Host from `~/.ssh/config` or known_hosts (for `ssh`, `scp`, `rsync`)
+    URL,              // URL (for `curl`, `wget`)
+    Device,           // Device name (for `mount`, `umount`, `lsblk`)
+    Disk,             // Disk name or partition (for `df`, `fsck`)
+    Shell,            // Shell type (for `chsh`)
+    Date,             // Date string (for `date`, `touch`)
+    Time,             // Time string (for scheduling or logging)
+    Signal,           // Signal type (e.g., `SIGKILL`, `SIGTERM`, for `kill -s`)
     History,
+    Branch,              // Local git branch (for `git checkout`, `git merge`)
+    RemoteBranch,        // Remote-tracking branch, `origin/main`
+    Commit,              // Abbreviated commit hash (for `git show`, `git revert`)
+    Tag,                 // Git tag
+    Remote,              // Git remote name (for `git push`, `git fetch`)
+    Stash,               // Stash entry, `stash@{0}`
+    ChangedFile,         // File with changes in `git status` (for `git add`, `git diff`)
+    TmuxSession,         // tmux session name (for `tmux attach -t`)
+    TmuxWindow,          // tmux window, `session:index`
+    TmuxPane,            // tmux pane, `session:window.pane`
+    K8sContext,          // kubeconfig context (for `kubectl --context`)
+    K8sNamespace,        // Kubernetes namespace (for `kubectl -n`)
+    Pod,                 // Pod in the chosen `<k8s_namespace>`
+    Deployment,          // Deployment in the chosen `<k8s_namespace>`
+    ServiceK8s,          // Kubernetes service in the chosen `<k8s_namespace>`
+    K8sContainer,        // Container of the chosen `<pod>` (for `kubectl logs -c`)
+    ContainerPort,       // Published port of the chosen `<container>`
+    FileInDir,           // File under the chosen `<directory>`
+    Choice(Vec<Choice>), // Inline list, `<env:{dev,staging,prod}>`
+    Source(String),      // Snippet-defined provider command, `<branch: git branch>`
     Unknown(String),
 }
+// Shows file contents with `bat` when it is installed.
+const FILE_PREVIEW: &str =
+    "command -v bat >/dev/null && bat --color=always --style=numbers {1} || head -n 100 {1}";
+
+/// Names of the placeholders with a built-in provider, as written in templates.
+pub const BUILT_IN_NAMES: &[&str] = &[
+    "PID",
+    "file",
+    "directory",
+    "container",
+    "image",
+    "volume",
+    "network",
+    "compose_service",
+    "port",
+    "free_port",
+    "user",
+    "group",
+    "command",
+    "package",
+    "available_package",
+    "interface",
+    "service",
+    "ip_address",
+    "ssh_host",
+    "url",
+    "device",
+    "disk",
+    "shell",
+    "date",
+    "time",
+    "history",
+    "signal",
+    "branch",
+    "remote_branch",
+    "commit",
+    "tag",
+    "remote",
+    "stash",
+    "changed_file",
+    "tmux_session",
+    "tmux_window",
+    "tmux_pane",
+    "k8s_context",
+    "k8s_namespace",
+    "pod",
+    "deployment",
+    "service_k8s",
+    "k8s_container",
+    "container_port",
+    "file_in_dir",
+];
+
+// Provider commands starting with these need nothing beyond the shell.
+const SHELL_BUILTINS: &[&str] = &["cd", "echo", "printf", "compgen", "history", "kill", "test"];
+
+/// Per-snippet settings for one placeholder's picker, layered over the provider's own.
+#[derive(Debug, Default)]
+pub struct Prompt {
+    pub multi: bool,                      // `<name...>`
+    pub preview: Option<String>,          // preview command from the config
+    pub header: Option<String>,           // the partially filled command
+    pub options: HashMap<String, String>, // `<PID --mine>`, read by built-in providers
+}
+
+/// Where a provider's items come from.
+enum Listing {
+    Command(String), // shell command printing one item per line
+    Rows(RowSource), // built into snipster, picked by the row's value
+}
+
+impl From<&str> for Listing {
+    fn from(command: &str) -> Self {
+        Listing::Command(command.to_string())
+    }
+}
+
+impl From<String> for Listing {
+    fn from(command: String) -> Self {
+        Listing::Command(command)
+    }
+}
+
 impl PlaceHolder {
-    pub fn extract_placeholders(content: &str) -> Result<Vec<PlaceHolder>, String> {
-        let placeholder_regex =
-            Regex::new(r"<[^>]+>").map_err(|e| format!("Invalid regex: {}", e))?;
+    pub fn extract_placeholders(
+        content: &str,
+        sources: &HashMap<String, String>,
+    ) -> Result<Vec<PlaceHolder>, SnipsterError> {
+        let template = Template::parse(content)?.with_sources(sources);
 
-        let placeholders: Vec<PlaceHolder> = placeholder_regex
-            .find_iter(content)
-            .map(|m| PlaceHolder::from_string(m.as_str()))
+        let placeholders: Vec<PlaceHolder> = template
+            .names()
+            .into_iter()
+            .map(|name| PlaceHolder::from_template(&template, name))
             .collect();
 
         Ok(placeholders)
     }
 
-    pub fn handle(&self) -> Result<String, SnipsterError> {
+    /// Asks for a value with the provider's picker, adjusted by `prompt`. A variadic
+    /// prompt returns the marked lines one per line.
+    pub fn handle(
+        &self,
+        resolved: &HashMap<String, String>,
+        prompt: &Prompt,
+    ) -> Result<String, SnipsterError> {
+        match self.provider() {
+            Some((mut fzf, listing)) => {
+                if prompt.multi {
+                    fzf = fzf.multi();
+                }
+                if let Some(ref preview) = prompt.preview {
+                    fzf = fzf.preview(preview);
+                }
+                if let Some(ref header) = prompt.header {
+                    fzf = fzf.ansi().header_text(header);
+                }
+                match listing {
+                    Listing::Command(command) => SnipsterCommand::fzf_with_command(
+                        fzf,
+                        Some(&bind_values(&command, resolved)),
+                    ),
+                    Listing::Rows(rows) => {
+                        let rows = rows(&prompt.options, resolved)?;
+                        SnipsterCommand::fzf_with_rows(fzf, &rows, resolved)
+                    }
+                }
+            }
+            None => Err(SnipsterError::CommandError(format!(
+                "Unknown placeholder: {:?}",
+                self
+            ))),
+        }
+    }
+
+    /// Placeholder names the provider command refers to as `{name}`; they are
+    /// resolved first and their values are substituted into the command. Only
+    /// names of built-in providers, of the template's placeholders or of its
+    /// `sources` count, so shell braces like `${HOME}` or awk's `{print}` don't.
+    /// Built-in providers that list the parts of another choice depend on it too.
+    pub fn dependencies(&self, template: &Template) -> Vec<String> {
+        let command = match self.provider() {
+            Some((_, Listing::Command(command))) => command,
+            _ if matches!(self, PlaceHolder::K8sContainer) => return vec!["pod".to_string()],
+            _ => return Vec::new(),
+        };
+        let names = template.names();
+
+        references(&command)
+            .into_iter()
+            .filter(|name| {
+                BUILT_IN_NAMES.contains(name)
+                    || names.contains(name)
+                    || template.sources.contains_key(*name)
+            })
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Placeholders whose values narrow the built-in provider's list, such as the
+    /// namespace of `<pod>`. They go first when the snippet has them, but unlike
+    /// dependencies are not asked for otherwise.
+    pub fn scope(&self) -> &'static [&'static str] {
         match self {
-            PlaceHolder::PID => {
-                let fzf = FzfBuilder::new()
-                    .ansi()
-                    .reverse()
-                    .header(1)
-                    .bind("enter:become(echo {2})");
-                SnipsterCommand::fzf_with_command(fzf, Some("ps aux"))
+            PlaceHolder::TmuxWindow => &["tmux_session"],
+            PlaceHolder::TmuxPane => &["tmux_session", "tmux_window"],
+            PlaceHolder::K8sNamespace => &["k8s_context"],
+            PlaceHolder::Pod | PlaceHolder::Deployment | PlaceHolder::ServiceK8s => {
+                &["k8s_context", "k8s_namespace"]
             }
-            PlaceHolder::File => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("find ~ -type f"))
+            PlaceHolder::K8sContainer => &["k8s_context", "k8s_namespace"],
+            _ => &[],
+        }
+    }
+
+    /// Orders the placeholders of `template` and everything they depend on so each
+    /// placeholder comes after its dependencies and scope.
+    pub fn resolution_order(template: &Template) -> Result<Vec<String>, SnipsterError> {
+        fn visit(
+            template: &Template,
+            name: &str,
+            path: &mut Vec<String>,
+            order: &mut Vec<String>,
+        ) -> Result<(), SnipsterError> {
+            if order.iter().any(|n| n == name) {
+                return Ok(());
             }
-            PlaceHolder::Directory => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("find ~ -type d"))
+            if let Some(start) = path.iter().position(|n| n == name) {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(name.to_string());
+                return Err(SnipsterError::PlaceHolderError(format!(
+                    "dependency cycle {}",
+                    cycle.join(" -> ")
+                )));
             }
-            PlaceHolder::Container => {
-                let fzf = FzfBuilder::new()
-                    .ansi()
+
+            path.push(name.to_string());
+            let placeholder = PlaceHolder::from_template(template, name);
+            let names = template.names();
+            let scope = placeholder
+                .scope()
+                .iter()
+                .filter(|scope| names.contains(scope))
+                .map(|scope| scope.to_string());
+            for dependency in placeholder.dependencies(template).into_iter().chain(scope) {
+                visit(template, &dependency, path, order)?;
+            }
+            path.pop();
+            order.push(name.to_string());
+
+            Ok(())
+        }
+
+        let mut order = Vec::new();
+        for name in template.names() {
+            visit(template, name, &mut Vec::new(), &mut order)?;
+        }
+
+        Ok(order)
+    }
+
+    /// Tools the built-in provider shells out to. A snippet-defined provider needs
+    /// the program its command starts with.
+    pub fn requires(&self) -> Vec<String> {
+        let tools: &[&str] = match self {
+            PlaceHolder::File | PlaceHolder::Directory | PlaceHolder::FileInDir => &["find"],
+            PlaceHolder::Container
+            | PlaceHolder::Image
+            | PlaceHolder::Volume
+            | PlaceHolder::Network
+            | PlaceHolder::ComposeService
+            | PlaceHolder::ContainerPort => return vec![docker::engine().to_string()],
+            PlaceHolder::User | PlaceHolder::Group => &["getent"],
+            PlaceHolder::IPAddress => &["ip"],
+            PlaceHolder::Service => &["systemctl"],
+            PlaceHolder::URL => &["curl"],
+            PlaceHolder::Branch
+            | PlaceHolder::RemoteBranch
+            | PlaceHolder::Commit
+            | PlaceHolder::Tag
+            | PlaceHolder::Remote
+            | PlaceHolder::Stash
+            | PlaceHolder::ChangedFile => &["git"],
+            PlaceHolder::TmuxSession | PlaceHolder::TmuxWindow | PlaceHolder::TmuxPane => &["tmux"],
+            PlaceHolder::K8sContext
+            | PlaceHolder::K8sNamespace
+            | PlaceHolder::Pod
+            | PlaceHolder::Deployment
+            | PlaceHolder::ServiceK8s
+            | PlaceHolder::K8sContainer => &["kubectl"],
+            PlaceHolder::Device | PlaceHolder::Disk => &["lsblk"],
+            PlaceHolder::Source(command) => {
+                return command
+                    .split_whitespace()
+                    .next()
+                    .filter(|program| {
+                        !SHELL_BUILTINS.contains(program)
+                            && program
+                                .chars()
+                                .all(|c| c.is_ascii_alphanumeric() || "_-./+".contains(c))
+                    })
+                    .map(|program| vec![program.to_string()])
+                    .unwrap_or_default()
+            }
+            _ => &[],
+        };
+
+        tools.iter().map(|tool| tool.to_string()).collect()
+    }
+
+    fn provider(&self) -> Option<(FzfBuilder, Listing)> {
+        let provider = match self {
+            PlaceHolder::PID => (
+                FzfBuilder::new()
                     .reverse()
                     .header(1)
-                    .bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("docker ps"))
-            }
-            PlaceHolder::Image => {
-                let fzf = FzfBuilder::new()
-                    .ansi()
+                    .preview(process::PREVIEW),
+                Listing::Rows(process::processes),
+            ),
+            PlaceHolder::File => (
+                FzfBuilder::new().preview(FILE_PREVIEW).accept("{1}"),
+                "find ~ -type f".into(),
+            ),
+            PlaceHolder::Directory => (
+                FzfBuilder::new().preview("ls -la {1}").accept("{1}"),
+                "find ~ -type d".into(),
+            ),
+            PlaceHolder::Container => (
+                FzfBuilder::new()
                     .reverse()
                     .header(1)
-                    .bind("enter:become(echo {3})");
-                SnipsterCommand::fzf_with_command(fzf, Some("docker images"))
-            }
-            PlaceHolder::Port => {
-                let fzf = FzfBuilder::new()
-                    .ansi()
+                    .preview(docker::CONTAINER_PREVIEW),
+                Listing::Rows(docker::containers),
+            ),
+            PlaceHolder::Image => (
+                FzfBuilder::new()
                     .reverse()
                     .header(1)
-                    .bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("netstat -tuln"))
-            }
-            PlaceHolder::User => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("getent passwd"))
-            }
-            PlaceHolder::Group => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("getent group"))
-            }
-            PlaceHolder::Command => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("compgen -c"))
-            }
-            PlaceHolder::Package => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("dpkg --get-selections"))
-            }
-            PlaceHolder::Interface => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("ip link show"))
-            }
-            PlaceHolder::Service => {
-                let fzf = FzfBuilder::new()
+                    .preview(docker::IMAGE_PREVIEW),
+                Listing::Rows(docker::images),
+            ),
+            PlaceHolder::Volume => (
+                FzfBuilder::new().ansi().preview(docker::VOLUME_PREVIEW),
+                Listing::Rows(docker::volumes),
+            ),
+            PlaceHolder::Network => (
+                FzfBuilder::new().ansi().preview(docker::NETWORK_PREVIEW),
+                Listing::Rows(docker::networks),
+            ),
+            PlaceHolder::ComposeService => (
+                FzfBuilder::new().ansi().preview(docker::COMPOSE_PREVIEW),
+                Listing::Rows(docker::compose_services),
+            ),
+            PlaceHolder::Port => (
+                FzfBuilder::new().reverse().header(1),
+                Listing::Rows(port::ports),
+            ),
+            PlaceHolder::FreePort => (FzfBuilder::new().reverse(), Listing::Rows(port::free_ports)),
+            PlaceHolder::User => (FzfBuilder::new().accept("{1}"), "getent passwd".into()),
+            PlaceHolder::Group => (FzfBuilder::new().accept("{1}"), "getent group".into()),
+            PlaceHolder::Command => (FzfBuilder::new().accept("{1}"), "compgen -c".into()),
+            PlaceHolder::Package => (
+                FzfBuilder::new().ansi().preview(package::INSTALLED_PREVIEW),
+                Listing::Rows(package::installed),
+            ),
+            PlaceHolder::AvailablePackage => (
+                FzfBuilder::new().ansi().preview(package::AVAILABLE_PREVIEW),
+                Listing::Rows(package::available),
+            ),
+            PlaceHolder::Interface => (
+                FzfBuilder::new()
+                    .reverse()
+                    .header(1)
+                    .preview("ip addr show {1} 2>/dev/null || ls -l /sys/class/net/{1}/"),
+                Listing::Rows(network::interfaces),
+            ),
+            PlaceHolder::Service => (
+                FzfBuilder::new()
                     .ansi()
                     .reverse()
                     .header(1)
-                    .bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("systemctl list-units --type=service"))
-            }
-            PlaceHolder::IPAddress => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("ip a"))
-            }
-            PlaceHolder::URL => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("curl --list-only"))
-            }
-            PlaceHolder::Device => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("lsblk"))
-            }
-            PlaceHolder::Disk => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("lsblk -o NAME,SIZE,TYPE,MOUNTPOINT"))
-            }
-            PlaceHolder::Shell => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("cat /etc/shells"))
-            }
-            PlaceHolder::Date => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("date"))
-            }
-            PlaceHolder::Time => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("date +'%H:%M:%S'"))
-            }
-            PlaceHolder::Signal => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("kill -l"))
+                    .preview("systemctl status --no-pager {1}")
+                    .accept("{1}"),
+                "systemctl list-units --type=service".into(),
+            ),
+            PlaceHolder::IPAddress => (
+                FzfBuilder::new().reverse().header(1),
+                Listing::Rows(network::addresses),
+            ),
+            PlaceHolder::SshHost => (
+                FzfBuilder::new().reverse().header(1).preview(ssh::PREVIEW),
+                Listing::Rows(ssh::hosts),
+            ),
+            PlaceHolder::URL => (FzfBuilder::new().accept("{1}"), "curl --list-only".into()),
+            PlaceHolder::Device => (FzfBuilder::new().accept("{1}"), "lsblk".into()),
+            PlaceHolder::Disk => (
+                FzfBuilder::new().accept("{1}"),
+                "lsblk -o NAME,SIZE,TYPE,MOUNTPOINT".into(),
+            ),
+            PlaceHolder::Shell => (FzfBuilder::new().accept("{1}"), "cat /etc/shells".into()),
+            PlaceHolder::Date => (FzfBuilder::new().accept("{1}"), "date".into()),
+            PlaceHolder::Time => (FzfBuilder::new().accept("{1}"), "date +'%H:%M:%S'".into()),
+            PlaceHolder::Signal => (FzfBuilder::new().accept("{1}"), "kill -l".into()),
+            PlaceHolder::History => (FzfBuilder::new().accept("{1}"), "history".into()),
+            PlaceHolder::Branch => (
+                FzfBuilder::new().ansi().preview(git::LOG_PREVIEW),
+                Listing::Rows(git::branches),
+            ),
+            PlaceHolder::RemoteBranch => (
+                FzfBuilder::new().ansi().preview(git::LOG_PREVIEW),
+                Listing::Rows(git::remote_branches),
+            ),
+            PlaceHolder::Commit => (
+                FzfBuilder::new().ansi().preview(git::SHOW_PREVIEW),
+                Listing::Rows(git::commits),
+            ),
+            PlaceHolder::Tag => (
+                FzfBuilder::new().ansi().preview(git::SHOW_PREVIEW),
+                Listing::Rows(git::tags),
+            ),
+            PlaceHolder::Remote => (
+                FzfBuilder::new().ansi().preview("git remote show {1}"),
+                Listing::Rows(git::remotes),
+            ),
+            PlaceHolder::Stash => (
+                FzfBuilder::new().ansi().preview(git::STASH_PREVIEW),
+                Listing::Rows(git::stashes),
+            ),
+            PlaceHolder::ChangedFile => (
+                FzfBuilder::new().ansi().preview(git::DIFF_PREVIEW),
+                Listing::Rows(git::changed_files),
+            ),
+            PlaceHolder::TmuxSession => (
+                FzfBuilder::new().ansi().preview(tmux::SESSION_PREVIEW),
+                Listing::Rows(tmux::sessions),
+            ),
+            PlaceHolder::TmuxWindow => (
+                FzfBuilder::new().ansi().preview(tmux::WINDOW_PREVIEW),
+                Listing::Rows(tmux::windows),
+            ),
+            PlaceHolder::TmuxPane => (
+                FzfBuilder::new().ansi().preview(tmux::PANE_PREVIEW),
+                Listing::Rows(tmux::panes),
+            ),
+            PlaceHolder::K8sContext => (
+                FzfBuilder::new()
+                    .ansi()
+                    .preview(kubernetes::CONTEXT_PREVIEW),
+                Listing::Rows(kubernetes::contexts),
+            ),
+            PlaceHolder::K8sNamespace => (
+                FzfBuilder::new()
+                    .ansi()
+                    .preview(kubernetes::NAMESPACE_PREVIEW),
+                Listing::Rows(kubernetes::namespaces),
+            ),
+            PlaceHolder::Pod => (
+                FzfBuilder::new().ansi().preview(kubernetes::POD_PREVIEW),
+                Listing::Rows(kubernetes::pods),
+            ),
+            PlaceHolder::Deployment => (
+                FzfBuilder::new()
+                    .ansi()
+                    .preview(kubernetes::DEPLOYMENT_PREVIEW),
+                Listing::Rows(kubernetes::deployments),
+            ),
+            PlaceHolder::ServiceK8s => (
+                FzfBuilder::new()
+                    .ansi()
+                    .preview(kubernetes::SERVICE_PREVIEW),
+                Listing::Rows(kubernetes::services),
+            ),
+            PlaceHolder::K8sContainer => (
+                FzfBuilder::new()
+                    .ansi()
+                    .preview(kubernetes::CONTAINER_PREVIEW),
+                Listing::Rows(kubernetes::containers),
+            ),
+            PlaceHolder::ContainerPort => (
+                FzfBuilder::new().delimiter("/").accept("{1}"),
+                format!("{} port {{container}}", docker::engine()).into(),
+            ),
+            PlaceHolder::FileInDir => (
+                FzfBuilder::new().preview(FILE_PREVIEW).accept("{1}"),
+                "find {directory} -type f".into(),
+            ),
+            PlaceHolder::Choice(choices) => {
+                let lines: Vec<String> = choices
+                    .iter()
+                    .flat_map(|choice| {
+                        [
+                            choice.value.as_str(),
+                            choice.description.as_deref().unwrap_or_default(),
+                        ]
+                    })
+                    .map(|field| Shell::Sh.quote(field, QuoteContext::Bare))
+                    .collect();
+
+                (
+                    FzfBuilder::new().delimiter(r"'\t'").accept("{1}"),
+                    format!(r"printf '%s\t%s\n' {}", lines.join(" ")).into(),
+                )
             }
-            PlaceHolder::History => {
-                let fzf = FzfBuilder::new().bind("enter:become(echo {1})");
-                SnipsterCommand::fzf_with_command(fzf, Some("history"))
+            PlaceHolder::Source(command) => {
+                (FzfBuilder::new().accept("{}"), command.clone().into())
             }
-            PlaceHolder::Unknown(val) => Err(SnipsterError::CommandError(format!(
-                "Unknown placeholder: {}",
-                val
-            ))),
-            // _ => Err(SnipsterError::CommandError(format!(
-            //     "No handler defined for this placeholder: {:?}",
-            //     self
-            // ))),
-        }
+            PlaceHolder::Unknown(_) => return None,
+        };
+
+        Some(provider)
     }
 
     pub fn replace_with_value(
         snippet: &Snippet,
-        values: &Vec<String>,
+        values: &[String],
     ) -> Result<String, SnipsterError> {
-        let mut command = snippet.content.clone(); // Start with the content of the snippet
+        let template = Template::parse(&snippet.content)?;
 
-        if snippet.placeholders.len() != values.len() {
-            return Err(SnipsterError::CommandError(
-                "Mismatch in the number of placeholders and values".into(),
-            ));
-        }
+        fill(&template, values, Shell::detect())
+    }
 
-        for (i, placeholder) in snippet.placeholders.iter().enumerate() {
-            let value = &values[i];
-            match placeholder {
-                PlaceHolder::Container => {
-                    command = command.replace("<container>", value);
-                }
-                PlaceHolder::PID => {
-                    command = command.replace("<PID>", value);
-                }
-                PlaceHolder::File => {
-                    command = command.replace("<file>", value);
-                }
-                PlaceHolder::Directory => {
-                    command = command.replace("<directory>", value);
-                }
-                PlaceHolder::Image => {
-                    command = command.replace("<image>", value);
-                }
-                PlaceHolder::Port => {
-                    command = command.replace("<port>", value);
-                }
-                PlaceHolder::User => {
-                    command = command.replace("<user>", value);
-                }
-                PlaceHolder::Group => {
-                    command = command.replace("<group>", value);
-                }
-                PlaceHolder::Command => {
-                    command = command.replace("<command>", value);
-                }
-                PlaceHolder::Package => {
-                    command = command.replace("<package>", value);
-                }
-                PlaceHolder::Interface => {
-                    command = command.replace("<interface>", value);
-                }
-                PlaceHolder::Service => {
-                    command = command.replace("<service>", value);
-                }
-                PlaceHolder::IPAddress => {
-                    command = command.replace("<ip_address>", value);
-                }
-                PlaceHolder::URL => {
-                    command = command.replace("<url>", value);
-                }
-                PlaceHolder::Device => {
-                    command = command.replace("<device>", value);
-                }
-                PlaceHolder::Disk => {
-                    command = command.replace("<disk>", value);
-                }
-                PlaceHolder::Shell => {
-                    command = command.replace("<shell>", value);
-                }
-                PlaceHolder::Date => {
-                    command = command.replace("<date>", value);
-                }
-                PlaceHolder::Time => {
-                    command = command.replace("<time>", value);
-                }
-                PlaceHolder::Signal => {
-                    command = command.replace("<signal>", value);
-                }
-                PlaceHolder::History => command = value.to_string(),
-                PlaceHolder::Unknown(s) => {
-                    command = command.replace(&format!("<{}>", s), value);
-                }
+    /// `snippet` with every placeholder replaced by its own name, quoted for bash
+    /// like a value but not filtered, so linting never runs a filter's command.
+    pub fn replace_with_names(snippet: &Snippet) -> Result<String, SnipsterError> {
+        let mut template = Template::parse(&snippet.content)?;
+        for segment in &mut template.segments {
+            if let Segment::Placeholder(node) = segment {
+                node.filters.clear();
             }
         }
+        let names: Vec<String> = template
+            .names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        fill(&template, &names, Shell::Bash)
+    }
+
+    /// The placeholder `name` stands for in `template`. An inline choice list or
+    /// command on any occurrence, then the snippet's `sources`, take precedence over
+    /// the built-in provider of that name.
+    pub fn from_template(template: &Template, name: &str) -> Self {
+        let inline = template
+            .placeholders()
+            .filter(|node| node.name == name)
+            .find_map(|node| match (&node.choices, &node.command) {
+                (Some(choices), _) => Some(PlaceHolder::Choice(choices.clone())),
+                (_, Some(command)) => Some(PlaceHolder::Source(command.clone())),
+                _ => None,
+            });
 
-        Ok(command) // Return the modified command
+        match (inline, template.sources.get(name)) {
+            (Some(placeholder), _) => placeholder,
+            (None, Some(command)) => PlaceHolder::Source(command.clone()),
+            (None, None) => PlaceHolder::from_string(name),
+        }
     }
 
-    fn from_string(s: &str) -> Self {
+    pub fn from_string(s: &str) -> Self {
         match s {
-            "<PID>" => PlaceHolder::PID,
-            "<file>" => PlaceHolder::File,
-            "<directory>" => PlaceHolder::Directory,
-            "<container>" => PlaceHolder::Container,
-            "<image>" => PlaceHolder::Image,
-            "<port>" => PlaceHolder::Port,
-            "<user>" => PlaceHolder::User,
-            "<group>" => PlaceHolder::Group,
-            "<command>" => PlaceHolder::Command,
-            "<package>" => PlaceHolder::Package,
-            "<interface>" => PlaceHolder::Interface,
-            "<service>" => PlaceHolder::Service,
-            "<ip_address>" => PlaceHolder::IPAddress,
-            "<url>" => PlaceHolder::URL,
-            "<device>" => PlaceHolder::Device,
-            "<disk>" => PlaceHolder::Disk,
-            "<shell>" => PlaceHolder::Shell,
-            "<date>" => PlaceHolder::Date,
-            "<time>" => PlaceHolder::Time,
-            "<history>" => PlaceHolder::History,
-            "<signal>" => PlaceHolder::Signal,
+            "PID" => PlaceHolder::PID,
+            "file" => PlaceHolder::File,
+            "directory" => PlaceHolder::Directory,
+            "container" => PlaceHolder::Container,
+            "image" => PlaceHolder::Image,
+            "volume" => PlaceHolder::Volume,
+            "network" => PlaceHolder::Network,
+            "compose_service" => PlaceHolder::ComposeService,
+            "port" => PlaceHolder::Port,
+            "free_port" => PlaceHolder::FreePort,
+            "user" => PlaceHolder::User,
+            "group" => PlaceHolder::Group,
+            "command" => PlaceHolder::Command,
+            "package" => PlaceHolder::Package,
+            "available_package" => PlaceHolder::AvailablePackage,
+            "interface" => PlaceHolder::Interface,
+            "service" => PlaceHolder::Service,
+            "ip_address" => PlaceHolder::IPAddress,
+            "ssh_host" => PlaceHolder::SshHost,
+            "url" => PlaceHolder::URL,
+            "device" => PlaceHolder::Device,
+            "disk" => PlaceHolder::Disk,
+            "shell" => PlaceHolder::Shell,
+            "date" => PlaceHolder::Date,
+            "time" => PlaceHolder::Time,
+            "history" => PlaceHolder::History,
+            "signal" => PlaceHolder::Signal,
+            "branch" => PlaceHolder::Branch,
+            "remote_branch" => PlaceHolder::RemoteBranch,
+            "commit" => PlaceHolder::Commit,
+            "tag" => PlaceHolder::Tag,
+            "remote" => PlaceHolder::Remote,
+            "stash" => PlaceHolder::Stash,
+            "changed_file" => PlaceHolder::ChangedFile,
+            "tmux_session" => PlaceHolder::TmuxSession,
+            "tmux_window" => PlaceHolder::TmuxWindow,
+            "tmux_pane" => PlaceHolder::TmuxPane,
+            "k8s_context" => PlaceHolder::K8sContext,
+            "k8s_namespace" => PlaceHolder::K8sNamespace,
+            "pod" => PlaceHolder::Pod,
+            "deployment" => PlaceHolder::Deployment,
+            "service_k8s" => PlaceHolder::ServiceK8s,
+            "k8s_container" => PlaceHolder::K8sContainer,
+            "container_port" => PlaceHolder::ContainerPort,
+            "file_in_dir" => PlaceHolder::FileInDir,
             _ => PlaceHolder::Unknown(s.to_string()), // Handle unknown placeholders
         }
     }
 }
+
+/// Substitutes `values`, lined up with the distinct placeholder names, into
+/// `template`. Filters run per occurrence, and every occurrence is quoted for
+/// its own position.
+fn fill(template: &Template, values: &[String], shell: Shell) -> Result<String, SnipsterError> {
+    let names = template.names();
+
+    if names.len() != values.len() {
+        return Err(SnipsterError::CommandError(
+            "Mismatch in the number of placeholders and values".into(),
+        ));
+    }
+
+    let mut command = String::new();
+    // Only the snippet's own text decides the quoting, not what earlier
+    // placeholders carry inline, like the `'` of `<m:{a=don't}>`.
+    let mut scanner = QuoteScanner::default();
+
+    for segment in &template.segments {
+        match segment {
+            Segment::Text(text) => {
+                command.push_str(text);
+                scanner.scan(text);
+            }
+            Segment::Placeholder(node) => {
+                let index = names.iter().position(|name| *name == node.name);
+                let value = index.map(|i| values[i].as_str()).unwrap_or_default();
+
+                if let PlaceHolder::History = PlaceHolder::from_string(&node.name) {
+                    return Ok(value.to_string());
+                }
+
+                // A variadic value holds one picked item per line; each is filtered
+                // and quoted on its own before joining.
+                let items: Vec<&str> = if node.multi {
+                    value.lines().collect()
+                } else {
+                    vec![value]
+                };
+
+                let context = scanner.context();
+                let mut rendered = Vec::with_capacity(items.len());
+                for item in items {
+                    let mut item = item.to_string();
+                    for filter in &node.filters {
+                        item = filter.apply(&item)?;
+                    }
+
+                    if node.raw {
+                        rendered.push(item);
+                    } else {
+                        rendered.push(shell.quote(&item, context));
+                    }
+                }
+
+                command.push_str(&rendered.join(&node.separator));
+                scanner.skip_value();
+            }
+        }
+    }
+
+    Ok(command) // Return the modified command
+}
+
+// `{name}`, with the character before it so `${name}` can be told apart.
+fn reference_regex() -> Regex {
+    Regex::new(r"(\$?)\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("valid reference pattern")
+}
+
+/// Names referred to as `{name}` in a provider command, leaving out shell
+/// parameter expansions `${name}`.
+fn references(command: &str) -> Vec<&str> {
+    reference_regex()
+        .captures_iter(command)
+        .filter(|c| c[1].is_empty())
+        .filter_map(|c| c.get(2))
+        .map(|name| name.as_str())
+        .collect()
+}
+
+/// Substitutes resolved `{name}` references into a provider command.
+fn bind_values(command: &str, resolved: &HashMap<String, String>) -> String {
+    reference_regex()
+        .replace_all(command, |c: &regex::Captures| {
+            match resolved.get(&c[2]).filter(|_| c[1].is_empty()) {
+                Some(value) => Shell::Sh.quote(value, QuoteContext::Bare),
+                None => c[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(content: &str) -> Vec<String> {
+        PlaceHolder::resolution_order(&Template::parse(content).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn shell_braces_are_not_dependencies() {
+        assert_eq!(order("ls <x: ls ${HOME}>"), vec!["x"]);
+        assert_eq!(order("echo <x: ls | awk '{print}'>"), vec!["x"]);
+    }
+
+    #[test]
+    fn references_resolve_first() {
+        assert_eq!(order("cat <f: ls {dir}> <dir>"), vec!["dir", "f"]);
+        // Built-in providers are asked for even when the snippet doesn't use them.
+        assert_eq!(
+            order("docker port <container_port>"),
+            vec!["container", "container_port"]
+        );
+        assert_eq!(
+            order("kubectl logs -c <k8s_container> -n <k8s_namespace>"),
+            vec!["k8s_namespace", "pod", "k8s_container"]
+        );
+    }
+
+    fn fill_in(content: &str, values: &[&str]) -> String {
+        let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+        fill(&Template::parse(content).unwrap(), &values, Shell::Bash).unwrap()
+    }
+
+    #[test]
+    fn quotes_inside_placeholders_do_not_change_the_context() {
+        assert_eq!(
+            fill_in("echo <m:{a=don't}> <file>", &["a", "a b; $(id)"]),
+            "echo a 'a b; $(id)'"
+        );
+        assert_eq!(
+            fill_in("echo <x: echo \"it's\"> <file>", &["it's", "a b; $(id)"]),
+            r"echo 'it'\''s' 'a b; $(id)'"
+        );
+        assert_eq!(
+            fill_in("echo '<x>' \"<y>\" <z>", &["it's", "$HOME", "a b"]),
+            r#"echo 'it'\''s' "\$HOME" 'a b'"#
+        );
+    }
+
+    #[test]
+    fn names_replace_placeholders_without_filters() {
+        let snippet = Snippet {
+            name: "since".into(),
+            content: "git log --since <date|fmt:%F> -- '<path>'".into(),
+            note: String::new(),
+            placeholders: Vec::new(),
+            sources: HashMap::new(),
+            danger: None,
+            requires: Vec::new(),
+        };
+
+        assert_eq!(
+            PlaceHolder::replace_with_names(&snippet).unwrap(),
+            "git log --since date -- 'path'"
+        );
+    }
+
+    #[test]
+    fn bind_values_skips_parameter_expansions() {
+        let resolved = HashMap::from([("dir".to_string(), "a b".to_string())]);
+        assert_eq!(
+            bind_values("ls {dir} ${dir} {other}", &resolved),
+            "ls 'a b' ${dir} {other}"
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+use std::env;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use super::{file::Snippet, placeholder::PlaceHolder, template::Template};
+
+/// Whether `tool` is an executable on `PATH`, or at the given path when it has a `/`.
+pub fn is_installed(tool: &str) -> bool {
+    if tool.contains('/') {
+        return is_executable(Path::new(tool));
+    }
+
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| is_executable(&dir.join(tool))))
+        .unwrap_or(false)
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+impl Snippet {
+    /// Tools the snippet needs: its own `requires`, then those implied by the
+    /// providers of its placeholders and their dependencies.
+    pub fn requirements(&self) -> Vec<String> {
+        let mut tools = self.requires.clone();
+
+        // A template that does not parse has no providers to check; using the
+        // snippet reports the error.
+        if let Ok(template) = Template::parse(&self.content) {
+            let template = template.with_sources(&self.sources);
+            for name in PlaceHolder::resolution_order(&template).unwrap_or_default() {
+                tools.extend(PlaceHolder::from_template(&template, &name).requires());
+            }
+        }
+
+        let mut seen = HashSet::new();
+        tools.retain(|tool| seen.insert(tool.clone()));
+        tools
+    }
+
+    pub fn missing_requirements(&self) -> Vec<String> {
+        self.requirements()
+            .into_iter()
+            .filter(|tool| !is_installed(tool))
+            .collect()
+    }
+}
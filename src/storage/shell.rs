@@ -0,0 +1,267 @@
+use std::env;
+
+/// The shell a resolved command is meant for. Quoting rules differ between
+/// POSIX shells and fish, so values are escaped for the detected target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Sh,
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// The quoting context a placeholder sits in within the snippet content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteContext {
+    Bare,   // <file>
+    Single, // '<file>'
+    Double, // "<file>"
+}
+
+impl Shell {
+    /// Detects the target shell from `SNIPSTER_SHELL`, falling back to `SHELL`.
+    pub fn detect() -> Self {
+        env::var("SNIPSTER_SHELL")
+            .or_else(|_| env::var("SHELL"))
+            .map(|path| Shell::from_name(&path))
+            .unwrap_or(Shell::Sh)
+    }
+
+    pub fn from_name(name: &str) -> Self {
+        match name.rsplit('/').next().unwrap_or(name) {
+            "bash" => Shell::Bash,
+            "zsh" => Shell::Zsh,
+            "fish" => Shell::Fish,
+            _ => Shell::Sh,
+        }
+    }
+
+    /// The program that runs commands quoted for this shell.
+    pub fn program(&self) -> &'static str {
+        match self {
+            Shell::Sh => "sh",
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+
+    /// Escapes `value` so it is inserted literally at a position with the given context.
+    pub fn quote(&self, value: &str, context: QuoteContext) -> String {
+        match (self, context) {
+            (_, QuoteContext::Bare) if is_safe(value) => value.to_string(),
+            (Shell::Fish, QuoteContext::Bare) => format!("'{}'", escape_fish_single(value)),
+            (_, QuoteContext::Bare) => format!("'{}'", value.replace('\'', r"'\''")),
+            (Shell::Fish, QuoteContext::Single) => escape_fish_single(value),
+            (_, QuoteContext::Single) => value.replace('\'', r"'\''"),
+            (Shell::Fish, QuoteContext::Double) => escape_double(value, &['\\', '"', '$']),
+            (Shell::Sh, QuoteContext::Double) => escape_double(value, &['\\', '"', '$', '`']),
+            // bash and zsh expand `!` inside double quotes, and a backslash does not
+            // suppress that reliably, so step out of the quotes for it instead.
+            (Shell::Bash | Shell::Zsh, QuoteContext::Double) => {
+                escape_double(value, &['\\', '"', '$', '`']).replace('!', r#""'!'""#)
+            }
+        }
+    }
+}
+
+/// Follows the quoting of a command read left to right, so the context can be
+/// taken at any point of it.
+#[derive(Debug, Clone, Copy)]
+pub struct QuoteScanner {
+    context: QuoteContext,
+    comment: bool,    // in a `#` comment, which runs to the end of the line
+    word_start: bool, // the next character starts a word, where `#` opens a comment
+}
+
+impl Default for QuoteScanner {
+    fn default() -> Self {
+        QuoteScanner {
+            context: QuoteContext::Bare,
+            comment: false,
+            word_start: true,
+        }
+    }
+}
+
+impl QuoteScanner {
+    pub fn scan(&mut self, text: &str) {
+        let mut chars = text.chars();
+
+        while let Some(c) = chars.next() {
+            if self.comment {
+                if c == '\n' {
+                    self.comment = false;
+                    self.word_start = true;
+                }
+                continue;
+            }
+
+            let word_start = std::mem::replace(&mut self.word_start, false);
+            match (self.context, c) {
+                (QuoteContext::Bare, '#') if word_start => self.comment = true,
+                (QuoteContext::Bare, '\\') | (QuoteContext::Double, '\\') => {
+                    chars.next();
+                }
+                (QuoteContext::Bare, '\'') => self.context = QuoteContext::Single,
+                (QuoteContext::Bare, '"') => self.context = QuoteContext::Double,
+                (QuoteContext::Single, '\'') | (QuoteContext::Double, '"') => {
+                    self.context = QuoteContext::Bare
+                }
+                (QuoteContext::Bare, c) if c.is_whitespace() || ";&|()".contains(c) => {
+                    self.word_start = true
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Moves past a value inserted at the current position, which continues the
+    /// word it starts.
+    pub fn skip_value(&mut self) {
+        self.word_start = false;
+    }
+
+    /// The context of the next character. A comment counts as bare.
+    pub fn context(&self) -> QuoteContext {
+        if self.comment {
+            QuoteContext::Bare
+        } else {
+            self.context
+        }
+    }
+}
+
+// A leading `=` is expanded to a command path by zsh.
+fn is_safe(value: &str) -> bool {
+    !value.is_empty()
+        && !value.starts_with('=')
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c))
+}
+
+fn escape_fish_single(value: &str) -> String {
+    value.replace('\\', r"\\").replace('\'', r"\'")
+}
+
+fn escape_double(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use QuoteContext::{Bare, Double, Single};
+
+    const SHELLS: [Shell; 4] = [Shell::Sh, Shell::Bash, Shell::Zsh, Shell::Fish];
+
+    #[test]
+    fn safe_values_stay_bare() {
+        for shell in SHELLS {
+            assert_eq!(shell.quote("src/main.rs", Bare), "src/main.rs");
+            assert_eq!(shell.quote("user@host:22", Bare), "user@host:22");
+            assert_eq!(shell.quote("key=value", Bare), "key=value");
+        }
+    }
+
+    #[test]
+    fn bare_values_are_single_quoted() {
+        for shell in [Shell::Sh, Shell::Bash, Shell::Zsh] {
+            assert_eq!(shell.quote("", Bare), "''");
+            assert_eq!(shell.quote("a b; $(id)", Bare), "'a b; $(id)'");
+            assert_eq!(shell.quote("don't", Bare), r"'don'\''t'");
+            // zsh would expand `=ls` to the path of `ls`.
+            assert_eq!(shell.quote("=ls", Bare), "'=ls'");
+        }
+        assert_eq!(Shell::Fish.quote("a b; $(id)", Bare), "'a b; $(id)'");
+        assert_eq!(Shell::Fish.quote(r"don't \", Bare), r"'don\'t \\'");
+        assert_eq!(Shell::Fish.quote("=ls", Bare), "'=ls'");
+    }
+
+    #[test]
+    fn single_quoted_values_close_and_reopen() {
+        for shell in [Shell::Sh, Shell::Bash, Shell::Zsh] {
+            assert_eq!(shell.quote("don't", Single), r"don'\''t");
+            assert_eq!(shell.quote(r"$x \", Single), r"$x \");
+        }
+        assert_eq!(Shell::Fish.quote(r"don't \", Single), r"don\'t \\");
+    }
+
+    #[test]
+    fn double_quoted_values_escape_expansions() {
+        let value = r#"$HOME `id` "q" \ !"#;
+        assert_eq!(
+            Shell::Sh.quote(value, Double),
+            r#"\$HOME \`id\` \"q\" \\ !"#
+        );
+        for shell in [Shell::Bash, Shell::Zsh] {
+            assert_eq!(
+                shell.quote(value, Double),
+                r#"\$HOME \`id\` \"q\" \\ "'!'""#
+            );
+        }
+        assert_eq!(
+            Shell::Fish.quote(value, Double),
+            r#"\$HOME `id` \"q\" \\ !"#
+        );
+    }
+
+    #[test]
+    fn sh_reads_quoted_values_back_literally() {
+        let value = "a b; $(id) `id` \"q\" don't \\ ! =x";
+        for (before, context, after) in [("", Bare, ""), ("'", Single, "'"), ("\"", Double, "\"")] {
+            let script = format!(
+                "printf %s {}{}{}",
+                before,
+                Shell::Sh.quote(value, context),
+                after
+            );
+            let output = Command::new("sh").arg("-c").arg(&script).output().unwrap();
+            assert_eq!(String::from_utf8_lossy(&output.stdout), value, "{}", script);
+        }
+    }
+
+    #[test]
+    fn context_follows_quotes_and_escapes() {
+        let at = |content: &str| {
+            let mut scanner = QuoteScanner::default();
+            scanner.scan(content);
+            scanner.context()
+        };
+
+        assert_eq!(at("echo "), Bare);
+        assert_eq!(at("echo '"), Single);
+        assert_eq!(at("echo \""), Double);
+        assert_eq!(at("echo 'a' "), Bare);
+        assert_eq!(at("echo \"it's "), Double);
+        assert_eq!(at("echo 'say \"hi "), Single);
+        assert_eq!(at("echo \\' "), Bare);
+        assert_eq!(at("echo \"a \\\" "), Double);
+        assert_eq!(at("echo '\\"), Single);
+    }
+
+    #[test]
+    fn context_skips_comments() {
+        let at = |content: &str| {
+            let mut scanner = QuoteScanner::default();
+            scanner.scan(content);
+            scanner.context()
+        };
+
+        assert_eq!(at("# don't\nrm "), Bare);
+        assert_eq!(at("ls; # it's \"quoted\"\necho '"), Single);
+        assert_eq!(at("echo # don't"), Bare);
+        // Only a `#` starting a word opens a comment.
+        assert_eq!(at("echo a#b'"), Single);
+        assert_eq!(at("echo '# x' \""), Double);
+    }
+}
@@ -0,0 +1,575 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+use super::filter::Filter;
+
+#[derive(Debug, Clone)]
+pub struct PlaceholderNode {
+    pub name: String,
+    pub raw: bool,                        // `<!name>`, inserted without shell quoting
+    pub multi: bool,                      // `<name...>`, picks several values
+    pub options: HashMap<String, String>, // `<PID --mine --name=ssh>`, flags map to ""
+    pub separator: String,                // `<name...|join:comma>`, joins the picked values
+    pub choices: Option<Vec<Choice>>,     // `<name:{a,b=description}>`, a fixed list to pick from
+    pub command: Option<String>,          // `<name: cmd>`, lists the values to pick from
+    pub filters: Vec<Filter>,             // `<name|basename|upper>`
+}
+
+/// One option of an inline choice list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Choice {
+    pub value: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Segment {
+    Text(String),
+    Placeholder(PlaceholderNode),
+}
+
+/// Parsed snippet content.
+///
+/// A placeholder is `<` directly followed by a name and closed by `>` on the same
+/// line. The name can be marked variadic with `...`, take ` --option` settings for
+/// its provider, carry an inline choice list `:{a,b}` or provider command `: cmd`,
+/// and be followed by `|filter` transforms.
+/// An inline command runs up to the closing `>`, pipes included, so it takes no
+/// filters. A `<` that is not followed by a name (`sort < in.txt`, `2>&1`,
+/// `<(cmd)`, `<<EOF`), or whose name does not go on into a placeholder closed on
+/// the same line (`sort <in.txt >out.txt`, `cat <input`), is plain text. A name
+/// running into other characters up to a `>` without whitespace (`<dest-dir>`) is
+/// an error. `\<` / `\>` always produce a literal `<` / `>`.
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub segments: Vec<Segment>,
+    pub sources: HashMap<String, String>, // provider commands from the snippet's `sources`
+}
+
+#[derive(Debug, Error)]
+#[error("line {line}, column {column}: {message}")]
+pub struct TemplateError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Template {
+    pub fn parse(content: &str) -> Result<Self, TemplateError> {
+        Parser::new(content).parse()
+    }
+
+    /// Adds provider commands keyed by placeholder name, as stored with a snippet.
+    pub fn with_sources(mut self, sources: &HashMap<String, String>) -> Self {
+        self.sources.extend(sources.clone());
+        self
+    }
+
+    pub fn placeholders(&self) -> impl Iterator<Item = &PlaceholderNode> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Placeholder(node) => Some(node),
+            Segment::Text(_) => None,
+        })
+    }
+
+    /// Renders the content for display while placeholders are being resolved:
+    /// resolved values are filled in and `current` is highlighted.
+    pub fn outline(&self, resolved: &HashMap<String, String>, current: &str) -> String {
+        let mut outline = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => outline.push_str(text),
+                Segment::Placeholder(node) if node.name == current => {
+                    outline.push_str(&format!("\x1b[1;33m<{}>\x1b[0m", node.name))
+                }
+                Segment::Placeholder(node) => match resolved.get(&node.name) {
+                    Some(value) => outline.push_str(&value.lines().collect::<Vec<_>>().join(" ")),
+                    None => outline.push_str(&format!("\x1b[2m<{}>\x1b[0m", node.name)),
+                },
+            }
+        }
+        outline
+    }
+
+    /// Whether any occurrence of `name` is variadic.
+    pub fn is_multi(&self, name: &str) -> bool {
+        self.placeholders()
+            .any(|node| node.name == name && node.multi)
+    }
+
+    /// Provider options given on any occurrence of `name`.
+    pub fn options(&self, name: &str) -> HashMap<String, String> {
+        self.placeholders()
+            .filter(|node| node.name == name)
+            .flat_map(|node| node.options.clone())
+            .collect()
+    }
+
+    /// Distinct placeholder names in order of first occurrence. Every occurrence of
+    /// a name shares one resolved value.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for node in self.placeholders() {
+            if !names.contains(&node.name.as_str()) {
+                names.push(&node.name);
+            }
+        }
+        names
+    }
+}
+
+struct Parser<'a> {
+    content: &'a str,
+    pos: usize,
+    segments: Vec<Segment>,
+    text: String,
+}
+
+impl<'a> Parser<'a> {
+    fn new(content: &'a str) -> Self {
+        Parser {
+            content,
+            pos: 0,
+            segments: Vec::new(),
+            text: String::new(),
+        }
+    }
+
+    fn parse(mut self) -> Result<Template, TemplateError> {
+        while let Some(c) = self.peek(0) {
+            match c {
+                '\\' if matches!(self.peek(1), Some('<' | '>')) => {
+                    self.pos += 1;
+                    self.push_text();
+                }
+                '<' if self.peek(1) == Some('<') => {
+                    // Heredocs and herestrings, consumed whole so `<<EOF` is not a placeholder.
+                    while self.peek(0) == Some('<') {
+                        self.push_text();
+                    }
+                }
+                '<' if self.starts_placeholder() => self.parse_placeholder()?,
+                '<' => {
+                    self.check_malformed()?;
+                    self.push_text();
+                }
+                _ => self.push_text(),
+            }
+        }
+
+        self.flush_text();
+
+        Ok(Template {
+            segments: self.segments,
+            sources: HashMap::new(),
+        })
+    }
+
+    /// Whether the `<` at the current position opens a placeholder: a name followed
+    /// by `>`, `...`, ` --option`, `:` or `|`, with a `>` later on the line. Shell
+    /// redirections such as `sort <in.txt` or `cat <input` stay text.
+    fn starts_placeholder(&self) -> bool {
+        let rest = &self.content[self.pos + 1..];
+        let rest = rest.strip_prefix('!').unwrap_or(rest);
+        if !rest.starts_with(is_name_start) {
+            return false;
+        }
+
+        let after_name = rest.trim_start_matches(is_name_char);
+        let after_name = after_name.strip_prefix("...").unwrap_or(after_name);
+        let line = after_name.split('\n').next().unwrap_or_default();
+
+        (line.starts_with(['>', ':', '|']) || line.starts_with(" --")) && line.contains('>')
+    }
+
+    /// Rejects `<dest-dir>`: a name and more characters without whitespace closed
+    /// by `>`, which was meant as a placeholder but can't be one.
+    fn check_malformed(&self) -> Result<(), TemplateError> {
+        let rest = &self.content[self.pos + 1..];
+        let rest = rest.strip_prefix('!').unwrap_or(rest);
+        if !rest.starts_with(is_name_start) {
+            return Ok(());
+        }
+        let Some(end) = rest.find(|c: char| c == '>' || c.is_whitespace()) else {
+            return Ok(());
+        };
+        if !rest[end..].starts_with('>') {
+            return Ok(());
+        }
+
+        let name_end = rest.find(|c: char| !is_name_char(c)).unwrap_or(end);
+        let offset = self.content.len() - rest.len() + name_end;
+        Err(self.error(
+            offset,
+            format!(
+                "invalid placeholder `<{}>`, names use only letters, digits and `_` \
+                 (write `\\<` for a literal `<`)",
+                &rest[..end]
+            ),
+        ))
+    }
+
+    fn parse_placeholder(&mut self) -> Result<(), TemplateError> {
+        self.flush_text();
+        let start = self.pos;
+        self.pos += 1;
+
+        let raw = self.peek(0) == Some('!');
+        if raw {
+            self.pos += 1;
+        }
+
+        let name_start = self.pos;
+        while self.peek(0).is_some_and(is_name_char) {
+            self.pos += 1;
+        }
+        let name = self.content[name_start..self.pos].to_string();
+
+        let multi = self.content[self.pos..].starts_with("...");
+        if multi {
+            self.pos += 3;
+        }
+
+        let mut options = HashMap::new();
+        while self.content[self.pos..].starts_with(" --") {
+            self.pos += 3;
+            let option = self.parse_option_text();
+            match option.split_once('=') {
+                Some((key, value)) => options.insert(key.to_string(), value.to_string()),
+                None => options.insert(option, String::new()),
+            };
+        }
+
+        let (choices, command) = match (self.peek(0), self.peek(1)) {
+            (Some(':'), Some('{')) => (Some(self.parse_choices(start, &name)?), None),
+            (Some(':'), _) => (None, Some(self.parse_command(start, &name)?)),
+            _ => (None, None),
+        };
+
+        let mut separator = " ".to_string();
+        let mut filters = Vec::new();
+        while self.peek(0) == Some('|') {
+            self.pos += 1;
+            let filter_start = self.pos;
+            let filter = self.parse_filter_text();
+
+            match filter.strip_prefix("join:") {
+                Some(_) if !multi => {
+                    return Err(self.error(
+                        filter_start,
+                        format!("`join` needs a variadic placeholder `<{}...>`", name),
+                    ))
+                }
+                Some(join) => separator = separator_from(join),
+                None => {
+                    filters.push(Filter::parse(&filter).map_err(|e| self.error(filter_start, e))?)
+                }
+            }
+        }
+
+        match self.peek(0) {
+            Some('>') => self.pos += 1,
+            Some('\n') | None => {
+                return Err(self.error(
+                    start,
+                    format!(
+                        "unterminated placeholder `<{}` (write `\\<` for a literal `<`)",
+                        name
+                    ),
+                ))
+            }
+            Some(c) => {
+                return Err(self.error(
+                    self.pos,
+                    format!(
+                        "unexpected `{}` in placeholder `<{}` (write `\\<` for a literal `<`)",
+                        c, name
+                    ),
+                ))
+            }
+        }
+
+        self.segments.push(Segment::Placeholder(PlaceholderNode {
+            name,
+            raw,
+            multi,
+            options,
+            separator,
+            choices,
+            command,
+            filters,
+        }));
+
+        Ok(())
+    }
+
+    /// Parses `:{TERM,KILL=force kill}`. `\,`, `\}` and `\=` keep the literal character.
+    fn parse_choices(&mut self, start: usize, name: &str) -> Result<Vec<Choice>, TemplateError> {
+        self.pos += 2;
+        let list_start = self.pos;
+
+        let mut items = Vec::new();
+        let mut item = String::new();
+        loop {
+            let c = match self.peek(0) {
+                Some('\n') | None => {
+                    return Err(
+                        self.error(start, format!("unterminated choice list for `<{}>`", name))
+                    )
+                }
+                Some(c) => c,
+            };
+            self.pos += c.len_utf8();
+
+            match c {
+                '\\' if matches!(self.peek(0), Some(',' | '}' | '=')) => {
+                    let escaped = self.peek(0).unwrap_or(c);
+                    self.pos += 1;
+                    // Keep `\=` escaped until the description is split off.
+                    if escaped == '=' {
+                        item.push('\\');
+                    }
+                    item.push(escaped);
+                }
+                ',' => items.push(std::mem::take(&mut item)),
+                '}' => {
+                    items.push(item);
+                    break;
+                }
+                c => item.push(c),
+            }
+        }
+
+        let choices: Vec<Choice> = items
+            .iter()
+            .map(|item| item.trim())
+            .filter(|item| !item.is_empty())
+            .map(Choice::parse)
+            .collect();
+
+        if choices.is_empty() {
+            return Err(self.error(list_start, format!("empty choice list for `<{}>`", name)));
+        }
+
+        Ok(choices)
+    }
+
+    /// Parses `: git branch --format='%(refname:short)'` up to the closing `>`.
+    fn parse_command(&mut self, start: usize, name: &str) -> Result<String, TemplateError> {
+        self.pos += 1;
+        let command_start = self.pos;
+
+        let mut command = String::new();
+        while let Some(c) = self.peek(0) {
+            let c = match c {
+                '\\' if self.peek(1) == Some('>') => {
+                    self.pos += 1;
+                    '>'
+                }
+                '>' | '\n' => break,
+                c => c,
+            };
+            command.push(c);
+            self.pos += c.len_utf8();
+        }
+
+        if command.trim().is_empty() {
+            return Err(self.error(
+                command_start,
+                format!("empty provider command for `<{}>`", name),
+            ));
+        }
+        if self.peek(0) != Some('>') {
+            return Err(self.error(
+                start,
+                format!("unterminated provider command for `<{}>`", name),
+            ));
+        }
+
+        Ok(command.trim().to_string())
+    }
+
+    fn parse_filter_text(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek(0) {
+            let c = match c {
+                // `\>` and `\|` keep a literal `>` or `|` in a filter argument.
+                '\\' if matches!(self.peek(1), Some('>' | '|')) => {
+                    self.pos += 1;
+                    self.peek(0).unwrap_or(c)
+                }
+                '|' | '>' | '\n' => break,
+                c => c,
+            };
+            text.push(c);
+            self.pos += c.len_utf8();
+        }
+        text
+    }
+
+    fn parse_option_text(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek(0)
+            .is_some_and(|c| !matches!(c, ' ' | '|' | '>' | ':' | '\n'))
+        {
+            self.pos += self.peek(0).map_or(1, char::len_utf8);
+        }
+        self.content[start..self.pos].to_string()
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.content[self.pos..].chars().nth(offset)
+    }
+
+    fn push_text(&mut self) {
+        if let Some(c) = self.peek(0) {
+            self.text.push(c);
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn flush_text(&mut self) {
+        if !self.text.is_empty() {
+            self.segments
+                .push(Segment::Text(std::mem::take(&mut self.text)));
+        }
+    }
+
+    fn error(&self, offset: usize, message: String) -> TemplateError {
+        let before = &self.content[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+
+        TemplateError {
+            line,
+            column,
+            message,
+        }
+    }
+}
+
+impl Choice {
+    fn parse(item: &str) -> Self {
+        let split = item
+            .char_indices()
+            .find(|&(i, c)| c == '=' && !item[..i].ends_with('\\'))
+            .map(|(i, _)| i);
+
+        let (value, description) = match split {
+            Some(i) => (&item[..i], Some(item[i + 1..].trim().replace("\\=", "="))),
+            None => (item, None),
+        };
+
+        Choice {
+            value: value.trim().replace("\\=", "="),
+            description,
+        }
+    }
+}
+
+fn separator_from(join: &str) -> String {
+    match join {
+        "space" => " ".to_string(),
+        "comma" => ",".to_string(),
+        "newline" => "\n".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(template: &Template) -> String {
+        template
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                Segment::Placeholder(node) => format!("[{}]", node.name),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn redirections_are_text() {
+        for content in [
+            "sort < in.txt > out.txt",
+            "sort <in.txt >out.txt",
+            "cat <input",
+            "cat <input >output",
+            "cmd 2>&1 | diff <(ls a) <(ls b)",
+            "cat <<EOF\nhello\nEOF",
+            "tr a-z A-Z <<<word",
+        ] {
+            let template = Template::parse(content).unwrap();
+            assert_eq!(template.names(), Vec::<&str>::new(), "{}", content);
+            assert_eq!(text(&template), content);
+        }
+    }
+
+    #[test]
+    fn escapes_produce_literal_brackets() {
+        let template = Template::parse(r"echo \<name\> <name>").unwrap();
+        assert_eq!(text(&template), "echo <name> [name]");
+    }
+
+    #[test]
+    fn placeholders_next_to_redirections() {
+        let template = Template::parse("sort <in.txt > <out> 2>&1").unwrap();
+        assert_eq!(template.names(), vec!["out"]);
+        assert_eq!(text(&template), "sort <in.txt > [out] 2>&1");
+    }
+
+    #[test]
+    fn placeholder_parts() {
+        let template =
+            Template::parse("kill -s <sig:{TERM,KILL=force}> <PID... --mine|join:comma|upper>")
+                .unwrap();
+        let nodes: Vec<&PlaceholderNode> = template.placeholders().collect();
+
+        let choices = nodes[0].choices.as_ref().unwrap();
+        assert_eq!(choices[1].value, "KILL");
+        assert_eq!(choices[1].description.as_deref(), Some("force"));
+
+        assert!(nodes[1].multi);
+        assert_eq!(nodes[1].separator, ",");
+        assert_eq!(nodes[1].options.get("mine").map(String::as_str), Some(""));
+        assert_eq!(nodes[1].filters.len(), 1);
+
+        let template = Template::parse("git checkout <b: git branch | cut -c3->").unwrap();
+        let node = template.placeholders().next().unwrap();
+        assert_eq!(node.command.as_deref(), Some("git branch | cut -c3-"));
+    }
+
+    #[test]
+    fn errors_point_at_line_and_column() {
+        let error = Template::parse("echo ok\nls <dir|nope>").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 9));
+        assert!(
+            error.message.contains("unknown filter"),
+            "{}",
+            error.message
+        );
+
+        let error = Template::parse("echo <x:{a>").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 6));
+
+        let error = Template::parse("cp <file>\n  <dest-dir>").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 8));
+        assert!(error.message.contains("<dest-dir>"), "{}", error.message);
+
+        let error = Template::parse("echo <x|join:comma>").unwrap_err();
+        assert!(error.message.contains("variadic"), "{}", error.message);
+    }
+}
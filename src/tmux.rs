@@ -0,0 +1,22 @@
+use std::process::Command;
+
+use crate::{error::SnipsterError, storage::file::Snipster};
+
+/// Types `command` into the tmux pane `target` without pressing enter, so it can
+/// be reviewed there before it runs.
+pub fn send_to_pane(target: &str, command: &str) -> Result<Snipster, SnipsterError> {
+    let output = Command::new("tmux")
+        .args(["send-keys", "-t", target, "-l", command])
+        .output()
+        .map_err(|e| SnipsterError::CommandError(format!("Failed to run tmux: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(SnipsterError::CommandError(format!(
+            "Cannot send the command to tmux pane `{}`: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(Snipster { snippet: None })
+}